@@ -1,107 +1,328 @@
 use crate::prelude::*;
 
-const CLASSIFICATION_THRESHOLD: usize = 8;
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VideoAnalyzer {
     pub video: Video,
-    pub language: String,
+    /// languages shown in the video, the one with the most screen time first
+    pub language: Vec<LanguageShare>,
+    #[serde(default)]
+    pub status: Status,
+    /// confidence, runner-ups and origin of the language with the most screen time
+    #[serde(default, rename = "languageClassification", skip_serializing_if = "Option::is_none")]
+    pub classification: Option<Classification>,
+    /// knowledge components of every language, keyed by the lowercase language
     #[serde(rename = "knowledgeComponents")]
-    pub knowledge_components: IndexSet<KnowledgeComponent>
+    pub knowledge_components: IndexMap<String, IndexSet<KnowledgeComponent>>,
+    /// source files reconstructed from the edits shown in the video
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<SourceFile>,
+    /// error that stopped the analysis, e.g. of a video within a playlist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// name of the output files, the title unless set otherwise, e.g. to keep the results of a batch apart
+    #[serde(skip)]
+    pub name: Option<String>,
+    #[serde(skip)]
+    #[cfg_attr(not(feature = "video"), allow(dead_code))]
+    pub source: Option<Arc<dyn VideoSource>>,
+    #[serde(skip)]
+    #[cfg_attr(not(feature = "video"), allow(dead_code))]
+    pub config: Arc<Config>,
 }
 
 impl VideoAnalyzer {
-    pub fn new(url: &str) -> Self {
+    pub fn from_source(source: Arc<dyn VideoSource>, video: &str, config: Arc<Config>) -> Result<Self, ExtractionError> {
+        let metadata = source.resolve(video)?;
+
+        Ok(Self::from_metadata(source, &metadata, config))
+    }
+
+    pub fn from_metadata(source: Arc<dyn VideoSource>, metadata: &VideoMetadata, config: Arc<Config>) -> Self {
         Self {
-            video: Video::new(url),
-            language: String::new(),
-            knowledge_components: IndexSet::new(),
+            video: Video::from_metadata(metadata),
+            language: Vec::new(),
+            status: Status::default(),
+            classification: None,
+            knowledge_components: IndexMap::new(),
+            sources: Vec::new(),
+            error: None,
+            name: None,
+            source: Some(source),
+            config: config,
         }
     }
 
-    pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
-        self.download_video()?;
-        let (sender, receiver) = mpsc::channel::<(Message, i32)>();
-        let url = self.video.url.clone();
+    pub fn from_file(file: &str, config: Arc<Config>) -> Result<Self, ExtractionError> {
+        let mut analyzer = Self::from_source(Arc::new(LocalFile::new()), file, config)?;
+        analyzer.video.local = true;
+
+        Ok(analyzer)
+    }
+
+    /// Runs the analysis, a video whose language cannot be classified is marked as unclassified
+    #[cfg(feature = "video")]
+    pub fn run(&mut self) -> Result<(), ExtractionError> {
+        self.fetch_video()?;
+        let (sender, receiver) = mpsc::sync_channel::<(Message, i32)>(self.config.pipeline.queue_size);
+        let metadata = self.video.metadata();
+        let locator = self.video.locator();
+        let config = self.config.clone();
 
         let handle = thread::spawn(move || {
-            parse_knowledge_components(receiver, url)
+            parse_knowledge_components(receiver, metadata, locator, config)
         });
         
-        Yolo::run(sender, &self.video.path)?;
+        Pipeline::run(sender, &self.video.path, &self.config)?;
 
-        let parser = handle.join().expect("Error joining handle");
-        self.knowledge_components = parser.get_knowledge_components();
-        self.language = parser.language.unwrap().to_string().to_lowercase();
+        let (router, reconstruction) = join_parser(handle)?;
+        let (language, status) = language_result(&router, &self.video.title);
+        self.language = language;
+        self.status = status;
+        self.classification = router.primary();
+        self.knowledge_components = router.knowledge_components();
+        self.sources = reconstruction.into_files();
+        for source in self.sources.iter_mut() {
+            source.language = router.language(source.region);
+        }
         
         Ok(())
     }
 
-    pub fn download_video(&self) -> Result<(), Box<dyn Error>> {
-        Command::new("yt-dlp")
-            .args(&["-f", "248", "-P", &format!("video//"), "-o", &format!("{}.mp4", self.video.title) , &format!("{}", self.video.url)]).output()?;
+    #[cfg(not(feature = "video"))]
+    pub fn run(&mut self) -> Result<(), ExtractionError> {
+        Err(ExtractionError::Config("video processing requires the `video` feature".into()))
+    }
+
+    /// Fetches the media of the video from its source into the video directory
+    #[cfg(feature = "video")]
+    pub fn fetch_video(&mut self) -> Result<(), ExtractionError> {
+        let source = self.source.as_ref().ok_or_else(|| ExtractionError::Download("video has no source to fetch from".into()))?;
+        let path = source.fetch(&self.video.metadata(), &current_dir()?.join("video"))?;
+        self.video.path = path.display().to_string();
 
         Ok(())
     }
 
-    pub fn save_result(&self) -> Result<(), Box<dyn Error>> {
-        let serialized = serde_json::to_string_pretty(&self)?;
-        create_dir("./output")?;
-        write(format!("./output/{}.json", self.video.title), serialized)?;
+    /// Writes the result and the reconstructed programs to the output directory and returns the path of the result
+    pub fn save_result(&self) -> Result<String, ExtractionError> {
+        let name = file_name(self.name.as_ref().unwrap_or(&self.video.title));
+        let serialized = serde_json::to_string_pretty(&self).map_err(|err| ExtractionError::Io(err.into()))?;
+        let file = format!("./output/{}.json", name);
+        create_dir_all("./output")?;
+        write(&file, serialized)?;
+
+        for source in self.sources.iter() {
+            let extension = source.language.map(|language| language.extension()).unwrap_or("txt");
+            write(format!("./output/{}_{}.{}", name, source.region, extension), source.program())?;
+        }
         
-        Ok(())
+        Ok(file)
     }
 }
 
-fn parse_knowledge_components(receiver: Receiver<(Message, i32)>, url: String) -> ProtoParser {
-    let mut parser = ProtoParser::new();
-    
-    if let Some(classification) = LanguageClassifier::classify(&url) {
-            parser.parse_language(&url, classification);
-            
-            loop {
-                if let Ok(message) = receiver.recv() {
-                    match message.0 {
-                        Message::StreamMessage(msg) => {
-                            parser.parse(&msg, message.1).unwrap();
-                        },
-                        Message::EndMessage => break,
+#[cfg(feature = "video")]
+pub(crate) fn parse_knowledge_components(receiver: Receiver<(Message, i32)>, metadata: VideoMetadata, locator: Locator, config: Arc<Config>) -> (LanguageRouter, SourceReconstruction) {
+    let mut router = LanguageRouter::new(&locator);
+    let mut reconstruction = SourceReconstruction::new();
+
+    // every region votes on its own language and its texts are buffered until its vote settled,
+    // the languages named in the metadata start every vote, so they tip uncertain votes without overriding the code
+    let prior = LanguageClassifier::classify_metadata(&metadata);
+    let mut votes: HashMap<usize, (LanguageVote, Vec<(String, i32)>)> = HashMap::new();
+    let mut samples = Vec::new();
+    let mut ended = HashSet::new();
+
+    // the loop ends on the end message or when the sender stopped early due to an error
+    while let Ok((message, time_code)) = receiver.recv() {
+        match message {
+            Message::StreamMessage(msg, region) => {
+                reconstruction.update(region.id, &msg, time_code);
+
+                if router.language(region.id).is_some() {
+                    parse_message(&mut router, &msg, time_code, region.id);
+                    continue;
+                }
+
+                let (vote, buffered) = votes.entry(region.id)
+                    .or_insert_with(|| (LanguageVote::new(&config.classification).with_prior(prior.as_ref()), Vec::new()));
+                vote.add(&msg);
+                buffered.push((msg, time_code));
+
+                // a region whose buffer is full before its vote settled takes its leading language or,
+                // if none of its texts were long enough to vote, the language of the video
+                let full = buffered.len() >= config.classification.max_votes;
+                let decision = vote.settled()
+                    .or_else(|| vote.result().filter(|_| full))
+                    .or_else(|| router.primary().filter(|_| full));
+
+                match decision {
+                    Some(classification) => {
+                        if let Some((_, buffered)) = votes.remove(&region.id) {
+                            router.assign(region.id, classification);
+                            replay(&mut router, region.id, buffered);
+                        }
                     }
+                    // without any language the oldest texts are dropped, so the buffer does not grow without bounds
+                    None if buffered.len() > config.classification.max_votes => {
+                        buffered.remove(0);
+                    }
+                    None => {}
                 }
-                thread::sleep(Duration::from_millis(500));
             }
-    }
-    else {
-        let mut classification_string = String::new();
-        let mut classify = true;
-        
-        loop {
-            if let Ok(message) = receiver.recv() {
-                match message.0 {
-                    Message::StreamMessage(msg) => {
-                        if classify {
-                            classification_string.push_str(&msg);
-                            
-                            if classification_string.chars().count() >= CLASSIFICATION_THRESHOLD {
-                                if let Some(classification) = LanguageClassifier::classify_ml(&msg) {
-                                    parser.parse_language(&url, classification);
-                                }
-                                classify = false;
-                            }
+            // an ended region gets no further texts, so its vote is decided right away if it has any
+            Message::TrackEndMessage(regions) => {
+                for region in regions {
+                    let decision = votes.get(&region)
+                        .and_then(|(vote, _)| vote.result().or_else(|| router.primary()));
+                    if let Some(classification) = decision {
+                        if let Some((_, buffered)) = votes.remove(&region) {
+                            router.assign(region, classification);
+                            replay(&mut router, region, buffered);
                         }
-                        parser.parse(&msg, message.1).unwrap();
-                    },
-                    Message::EndMessage => break,
+                    }
+                    ended.insert(region);
                 }
-            }  
-            thread::sleep(Duration::from_millis(500));
+            }
+            // every region of a sample is lexed with its own language, so samples wait until each of their regions
+            // is classified or ended, e.g. dropped by the tracker before it was confirmed
+            Message::DebugMessage(sample) => samples.push(sample),
+            Message::EndMessage => break,
+        }
+        let (done, waiting): (Vec<DebugSample>, _) = samples.into_iter()
+            .partition(|sample| sample.regions.iter().all(|region| router.language(*region).is_some() || ended.contains(region)));
+        samples = waiting;
+        for sample in done {
+            write_debug_sample(&router, sample);
+        }
+    }
+
+    // a vote that did not settle until the end of the video takes the leading language,
+    // a region too short to vote on is parsed as the language of the video
+    let mut unsettled: Vec<_> = votes.into_iter().collect();
+    unsettled.sort_by_key(|(region, _)| *region);
+    let mut undecided = Vec::new();
+    for (region, (vote, buffered)) in unsettled {
+        match vote.result() {
+            Some(classification) => {
+                router.assign(region, classification);
+                replay(&mut router, region, buffered);
+            }
+            None => undecided.push((region, buffered)),
+        }
+    }
+    if let Some(classification) = router.primary() {
+        for (region, buffered) in undecided {
+            router.assign(region, classification.clone());
+            replay(&mut router, region, buffered);
+        }
+    }
+    // regions never classified, e.g. dropped by the tracker, have no tokens
+    for sample in samples {
+        write_debug_sample(&router, sample);
+    }
+
+    (router, reconstruction)
+}
+
+/// Parses the buffered texts of a region in their order
+#[cfg(feature = "video")]
+fn replay(router: &mut LanguageRouter, region: usize, buffered: Vec<(String, i32)>) {
+    for (msg, time_code) in buffered {
+        parse_message(router, &msg, time_code, region);
+    }
+}
+
+/// Parses a single message, a failing message is logged and skipped
+#[cfg(feature = "video")]
+fn parse_message(router: &mut LanguageRouter, msg: &str, time_code: i32, region: usize) {
+    if let Err(err) = router.parse(msg, time_code, region) {
+        eprintln!("Error: {}", err);
+    }
+}
+
+#[cfg(feature = "video")]
+pub(crate) fn join_parser(handle: thread::JoinHandle<(LanguageRouter, SourceReconstruction)>) -> Result<(LanguageRouter, SourceReconstruction), ExtractionError> {
+    handle.join().map_err(|_| ExtractionError::Parse("parser thread panicked".into()))
+}
+
+/// Returns the language of a parser classified as a whole as a single share and the status of the classification
+pub(crate) fn classification_result(parser: &ProtoParser, title: &str) -> (Vec<LanguageShare>, Status) {
+    match parser.classification.as_ref() {
+        Some(classification) => {
+            let share = LanguageShare {
+                language: classification.language,
+                share: 1.,
+                confidence: classification.confidence,
+                source: classification.source,
+                regions: Vec::new(),
+            };
+            (vec![share], Status::Classified)
+        }
+        None => {
+            eprintln!("Warning: programming language of {} could not be classified", title);
+            (Vec::new(), Status::Unclassified)
         }
-    }   
-    parser
+    }
+}
+
+/// Returns the screen-time shares of the languages and the status of the classification
+#[cfg(feature = "video")]
+pub(crate) fn language_result(router: &LanguageRouter, title: &str) -> (Vec<LanguageShare>, Status) {
+    let shares = router.shares();
+    if shares.is_empty() {
+        eprintln!("Warning: programming language of {} could not be classified", title);
+        return (shares, Status::Unclassified);
+    }
+
+    (shares, Status::Classified)
+}
+
+#[cfg(feature = "video")]
+fn write_debug_sample(router: &LanguageRouter, mut sample: DebugSample) {
+    sample.tokens = sample.regions.iter()
+        .zip(sample.texts.iter())
+        .map(|(region, text)| router.tokens(*region, text))
+        .collect();
+
+    if let Err(err) = sample.append_to_manifest() {
+        eprintln!("Error writing debug manifest: {}", err);
+    }
 }
 
+/// Outcome of the language classification, unclassified results carry no knowledge components
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Classified,
+    #[default]
+    Unclassified,
+}
+
+/// Detected source code region of a frame, the id is unique within its frame
+#[cfg(feature = "video")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Region {
+    pub id: usize,
+    pub bbox: BoundingBox,
+    pub confidence: f32,
+}
+
+#[cfg(feature = "video")]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[cfg(feature = "video")]
 pub enum Message {
-    StreamMessage(String),
+    /// recognized text of a single region
+    StreamMessage(String, Region),
+    /// ids of the regions whose tracks ended, no further texts follow for them
+    TrackEndMessage(Vec<usize>),
+    DebugMessage(DebugSample),
     EndMessage,
 }
 
@@ -110,29 +331,150 @@ pub struct Video {
     pub title: String,
     pub url: String,
     #[serde(skip)]
-    pub path: String
+    pub path: String,
+    #[serde(skip)]
+    pub local: bool,
+    #[serde(skip)]
+    #[cfg_attr(not(feature = "video"), allow(dead_code))]
+    pub description: String,
+    #[serde(skip)]
+    #[cfg_attr(not(feature = "video"), allow(dead_code))]
+    pub tags: Vec<String>,
 }
 
 impl Video {
-    fn new(url: &str) -> Self {
-        let video_title = get_video_title(url);
-        let path = format!("{}/video/{}.mp4", current_dir().unwrap().display().to_string(), video_title);
-
+    fn from_metadata(metadata: &VideoMetadata) -> Self {
         Self {
-            title: video_title,
-            url: url.into(),
-            path: path
+            title: metadata.title.clone(),
+            url: metadata.url.clone(),
+            path: metadata.path.clone().unwrap_or_default(),
+            local: false,
+            description: metadata.description.clone(),
+            tags: metadata.tags.clone(),
+        }
+    }
+
+    #[cfg(feature = "video")]
+    pub fn metadata(&self) -> VideoMetadata {
+        VideoMetadata {
+            title: self.title.clone(),
+            url: self.url.clone(),
+            path: Some(self.path.clone()).filter(|path| !path.is_empty()),
+            description: self.description.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+
+    #[cfg(feature = "video")]
+    pub fn locator(&self) -> Locator {
+        if self.local {
+            Locator::File(self.path.clone())
+        } else {
+            Locator::Url(self.url.clone())
         }
     }
 }
 
-fn get_video_title(url: &str) -> String {
-    let command_output = Command::new("yt-dlp").args(&["--print", "title", &format!("{}", url)]).output().expect("Error: youtube-dl could not get video title.");
-    let mut video_title = from_utf8(&command_output.stdout).unwrap().to_owned();
+/// Replaces the characters of a title that are not allowed in file names, e.g. the slash of "TCP/IP in C"
+pub(crate) fn file_name(title: &str) -> String {
+    let name: String = title.chars()
+        .map(|c| if c.is_control() || "/\\:*?\"<>|".contains(c) { '_' } else { c })
+        .collect();
+    // a name of dots only would point to a directory
+    let name = name.trim().trim_start_matches('.');
+
+    if name.is_empty() { "_".into() } else { name.into() }
+}
+
+pub(crate) fn get_file_title(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.into())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_name_replaces_path_separators() {
+        assert_eq!(file_name("TCP/IP in C"), "TCP_IP in C");
+        assert_eq!(file_name("C:\\Users \"quoted\" <a|b>?*"), "C__Users _quoted_ _a_b___");
+        assert_eq!(file_name("Python 3 tutorial"), "Python 3 tutorial");
+        assert_eq!(file_name(".."), "_");
+        assert_eq!(file_name("../secret"), "_secret");
+    }
+
+    #[cfg(feature = "video")]
+    const C_PROGRAM: &str = "#include <stdio.h>\nint main(void) {\n    printf(\"%d\\n\", 1);\n    return 0;\n}";
+
+    /// Parses the texts, given as region and text, the time code is their index
+    #[cfg(feature = "video")]
+    fn parse(metadata: VideoMetadata, classification: ClassificationConfig, texts: &[(usize, &str)]) -> LanguageRouter {
+        let (sender, receiver) = mpsc::sync_channel(texts.len() + 1);
+        for (time_code, (region, text)) in texts.iter().enumerate() {
+            let region = Region {
+                id: *region,
+                bbox: BoundingBox { x: 0, y: 0, width: 100, height: 100 },
+                confidence: 1.,
+            };
+            sender.send((Message::StreamMessage(text.to_string(), region), time_code as i32)).unwrap();
+        }
+        sender.send((Message::EndMessage, 0)).unwrap();
+
+        let config = Config { classification: classification, ..Config::default() };
+        let (router, _) = parse_knowledge_components(receiver, metadata, Locator::File("v.mp4".into()), Arc::new(config));
+        router
+    }
+
+    /// Time stamps of the knowledge components of the language, keyed by token
+    #[cfg(feature = "video")]
+    fn time_stamps(router: &LanguageRouter, language: &str) -> HashMap<String, String> {
+        let components = serde_json::to_value(&router.knowledge_components()[language]).unwrap();
+        components.as_array().unwrap().iter()
+            .map(|component| (component["token"].as_str().unwrap().into(), component["timeStamp"].as_str().unwrap().into()))
+            .collect()
+    }
+
+    #[cfg(feature = "video")]
+    #[test]
+    fn buffered_texts_are_replayed_once_the_vote_settled() {
+        let classification = ClassificationConfig { min_votes: 2, ..ClassificationConfig::default() };
+        let router = parse(VideoMetadata::new("lecture", "v.mp4", None), classification, &[(0, "i++;"), (0, C_PROGRAM), (0, C_PROGRAM), (0, "j--;")]);
 
-    if video_title.ends_with("\n") {
-        video_title.pop();
+        assert_eq!(router.language(0), Some(ProgrammingLanguage::C));
+        let time_stamps = time_stamps(&router, "c");
+        // the texts before the vote settled are parsed with the chosen language
+        assert_eq!(time_stamps["PostfixIncrement"], "v.mp4#t=0");
+        assert_eq!(time_stamps["Return"], "v.mp4#t=1");
+        assert_eq!(time_stamps["PostfixDecrement"], "v.mp4#t=3");
     }
 
-    video_title
-}
\ No newline at end of file
+    #[cfg(feature = "video")]
+    #[test]
+    fn region_too_short_to_vote_takes_the_video_language() {
+        let classification = ClassificationConfig { min_votes: 2, max_votes: 3, ..ClassificationConfig::default() };
+        let mut texts = vec![(1, "i++;"); 6];
+        texts.extend(vec![(0, C_PROGRAM); 2]);
+        texts.extend(vec![(2, "j--;"); 4]);
+        let router = parse(VideoMetadata::new("lecture", "v.mp4", None), classification, &texts);
+
+        assert_eq!(router.language(1), Some(ProgrammingLanguage::C));
+        assert_eq!(router.language(2), Some(ProgrammingLanguage::C));
+        let time_stamps = time_stamps(&router, "c");
+        // until the first region chose its language, only the latest texts of a region are kept
+        assert_eq!(time_stamps["PostfixIncrement"], "v.mp4#t=3");
+        assert_eq!(time_stamps["PostfixDecrement"], "v.mp4#t=8");
+    }
+
+    #[cfg(feature = "video")]
+    #[test]
+    fn code_outvotes_the_language_of_the_title() {
+        const PYTHON_PROGRAM: &str = "def main():\n    print('hi')\n\nif __name__ == '__main__':\n    main()";
+        let classification = ClassificationConfig { min_votes: 2, ..ClassificationConfig::default() };
+        let texts = vec![(0, PYTHON_PROGRAM); 4];
+        let router = parse(VideoMetadata::new("C programming for beginners", "v.mp4", None), classification, &texts);
+
+        assert_eq!(router.language(0), Some(ProgrammingLanguage::Python));
+    }
+}
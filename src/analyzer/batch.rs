@@ -0,0 +1,185 @@
+use crate::prelude::*;
+
+const VIDEO_EXTENSIONS: [&str; 7] = ["mp4", "mkv", "webm", "avi", "mov", "m4v", "flv"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchAnalyzer {
+    pub path: String,
+    pub videos: Vec<BatchEntry>,
+    #[serde(skip)]
+    pub recursive: bool,
+    #[serde(skip)]
+    pub config: Arc<Config>,
+}
+
+/// Summary of a single analyzed video within a batch
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchEntry {
+    pub title: String,
+    pub path: String,
+    /// lowercase language with the most screen time
+    pub language: String,
+    #[serde(default)]
+    pub status: Status,
+    /// probability of the classified language
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    #[serde(rename = "knowledgeComponents")]
+    pub knowledge_components: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BatchAnalyzer {
+    pub fn new(path: &str, recursive: bool, config: Arc<Config>) -> Self {
+        Self {
+            path: path.into(),
+            videos: Vec::new(),
+            recursive,
+            config,
+        }
+    }
+
+    /// Runs the analysis for every video in the directory, a failing video is logged and skipped
+    pub fn run(&mut self) -> Result<(), ExtractionError> {
+        let files = find_video_files(Path::new(&self.path), self.recursive)?;
+
+        for file in files {
+            let path = file.display().to_string();
+            println!("Analyzing {}", path);
+
+            match analyze_file(&path, &output_name(Path::new(&self.path), &file), self.config.clone()) {
+                Ok((analyzer, result)) => {
+                    self.videos.push(BatchEntry {
+                        title: analyzer.video.title,
+                        path,
+                        language: analyzer.language.first().map(|share| share.language.to_string().to_lowercase()).unwrap_or_default(),
+                        status: analyzer.status,
+                        confidence: analyzer.classification.as_ref().map(|classification| classification.confidence),
+                        knowledge_components: analyzer.knowledge_components.values().map(|components| components.len()).sum(),
+                        result: Some(result),
+                        error: None,
+                    });
+                }
+                Err(err) => {
+                    eprintln!("Error analyzing {}: {}", path, err);
+                    self.videos.push(BatchEntry {
+                        title: get_file_title(&path),
+                        path,
+                        language: String::new(),
+                        status: Status::Unclassified,
+                        confidence: None,
+                        knowledge_components: 0,
+                        result: None,
+                        error: Some(err.to_string()),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the summary index of all analyzed videos
+    pub fn save_result(&self) -> Result<(), ExtractionError> {
+        let serialized = serde_json::to_string_pretty(&self).map_err(|err| ExtractionError::Io(err.into()))?;
+        let name = get_file_title(&std::fs::canonicalize(&self.path)?.display().to_string());
+        create_dir_all("./output")?;
+        write(format!("./output/{}_index.json", file_name(&name)), serialized)?;
+
+        Ok(())
+    }
+}
+
+fn analyze_file(path: &str, name: &str, config: Arc<Config>) -> Result<(VideoAnalyzer, String), ExtractionError> {
+    let mut analyzer = VideoAnalyzer::from_file(path, config)?;
+    analyzer.name = Some(name.into());
+    analyzer.run()?;
+    let result = analyzer.save_result()?;
+
+    Ok((analyzer, result))
+}
+
+fn find_video_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>, ExtractionError> {
+    let mut files = Vec::new();
+
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            if recursive {
+                files.extend(find_video_files(&path, recursive)?);
+            }
+        } else if is_video_file(&path) {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+/// Name of the output files of a video, videos of the same name in different subdirectories must not overwrite each other,
+/// so the name is the path relative to the batch directory without extension, e.g. `a_lecture1` for `a/lecture1.mp4`
+fn output_name(dir: &Path, file: &Path) -> String {
+    relative_name(dir, &file.with_extension(""))
+}
+
+/// Path of the file relative to the directory, its components joined by `_`
+pub(crate) fn relative_name(dir: &Path, file: &Path) -> String {
+    file.strip_prefix(dir).unwrap_or(file)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn is_video_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| VIDEO_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_video_extensions() {
+        assert!(is_video_file(Path::new("lecture.mp4")));
+        assert!(is_video_file(Path::new("a/lecture.MKV")));
+        assert!(!is_video_file(Path::new("lecture.info.json")));
+        assert!(!is_video_file(Path::new("lecture")));
+    }
+
+    #[test]
+    fn finds_videos_of_subdirectories_if_recursive() {
+        let dir = test_dir("batch", "find");
+        write_files(&dir, &[("b.mp4", ""), ("a.webm", ""), ("notes.txt", ""), ("sub/c.mov", ""), ("sub/d.json", "")]);
+
+        let flat = find_video_files(&dir, false).unwrap();
+        assert_eq!(flat, vec![dir.join("a.webm"), dir.join("b.mp4")]);
+
+        let recursive = find_video_files(&dir, true).unwrap();
+        assert_eq!(recursive, vec![dir.join("a.webm"), dir.join("b.mp4"), dir.join("sub/c.mov")]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn output_names_of_equal_file_names_differ() {
+        let dir = test_dir("batch", "names");
+        write_files(&dir, &[("a/lecture1.mp4", ""), ("b/lecture1.mp4", "")]);
+        let names: Vec<_> = find_video_files(&dir, true).unwrap().iter()
+            .map(|file| output_name(&dir, file))
+            .collect();
+
+        assert_eq!(names, vec!["a_lecture1", "b_lecture1"]);
+        assert_eq!(output_name(&dir, &dir.join("lecture2.mp4")), "lecture2");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
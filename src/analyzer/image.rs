@@ -0,0 +1,114 @@
+use crate::prelude::*;
+
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "bmp", "tif", "tiff", "webp"];
+
+/// Analyzes a single image or a directory of slides and screenshots
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImageAnalyzer {
+    pub video: Video,
+    /// languages shown in the images, the one shown the longest first
+    pub language: Vec<LanguageShare>,
+    #[serde(default)]
+    pub status: Status,
+    /// confidence, runner-ups and origin of the language shown the longest
+    #[serde(default, rename = "languageClassification", skip_serializing_if = "Option::is_none")]
+    pub classification: Option<Classification>,
+    /// knowledge components of every language, keyed by the lowercase language
+    #[serde(rename = "knowledgeComponents")]
+    pub knowledge_components: IndexMap<String, IndexSet<KnowledgeComponent>>,
+    #[serde(skip)]
+    #[cfg_attr(not(feature = "video"), allow(dead_code))]
+    pub images: Vec<String>,
+    #[serde(skip)]
+    #[cfg_attr(not(feature = "video"), allow(dead_code))]
+    pub config: Arc<Config>,
+}
+
+impl ImageAnalyzer {
+    pub fn new(input: &str, config: Arc<Config>) -> Result<Self, ExtractionError> {
+        let path = std::fs::canonicalize(input)?;
+        let images = if path.is_dir() {
+            find_image_files(&path)?
+        } else {
+            vec![path.display().to_string()]
+        };
+        let path = path.display().to_string();
+
+        Ok(Self {
+            video: Video {
+                title: get_file_title(&path),
+                url: path.clone(),
+                path: path,
+                local: true,
+                description: String::new(),
+                tags: Vec::new(),
+            },
+            language: Vec::new(),
+            status: Status::default(),
+            classification: None,
+            knowledge_components: IndexMap::new(),
+            images: images,
+            config: config,
+        })
+    }
+
+    #[cfg(feature = "video")]
+    pub fn run(&mut self) -> Result<(), ExtractionError> {
+        let (sender, receiver) = mpsc::sync_channel::<(Message, i32)>(self.config.pipeline.queue_size);
+        let metadata = self.video.metadata();
+        let names = self.images.iter()
+            .map(|image| Path::new(image).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default())
+            .collect();
+        let locator = Locator::Image(names);
+        let config = self.config.clone();
+
+        let handle = thread::spawn(move || {
+            parse_knowledge_components(receiver, metadata, locator, config)
+        });
+
+        Pipeline::run_images(sender, &self.images, &self.config)?;
+
+        let (router, _) = join_parser(handle)?;
+        let (language, status) = language_result(&router, &self.video.title);
+        self.language = language;
+        self.status = status;
+        self.classification = router.primary();
+        self.knowledge_components = router.knowledge_components();
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "video"))]
+    pub fn run(&mut self) -> Result<(), ExtractionError> {
+        Err(ExtractionError::Config("image processing requires the `video` feature".into()))
+    }
+
+    /// Writes the result to the output directory and returns the path of the written file
+    pub fn save_result(&self) -> Result<String, ExtractionError> {
+        let serialized = serde_json::to_string_pretty(&self).map_err(|err| ExtractionError::Io(err.into()))?;
+        let file = format!("./output/{}.json", file_name(&self.video.title));
+        create_dir_all("./output")?;
+        write(&file, serialized)?;
+
+        Ok(file)
+    }
+}
+
+fn find_image_files(dir: &Path) -> Result<Vec<String>, ExtractionError> {
+    let mut files = Vec::new();
+
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        let is_image = path.extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+            .unwrap_or(false);
+
+        if path.is_file() && is_image {
+            files.push(path.display().to_string());
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
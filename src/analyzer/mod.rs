@@ -1 +1,5 @@
-pub(crate) mod analyzer;
\ No newline at end of file
+pub(crate) mod analyzer;
+pub(crate) mod batch;
+pub(crate) mod image;
+pub(crate) mod playlist;
+pub(crate) mod text;
\ No newline at end of file
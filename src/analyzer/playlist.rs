@@ -0,0 +1,130 @@
+use crate::prelude::*;
+
+/// Course level result containing every video of a playlist
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlaylistAnalyzer {
+    pub title: String,
+    pub url: String,
+    pub videos: Vec<VideoAnalyzer>,
+}
+
+impl PlaylistAnalyzer {
+    /// Expands the playlist, its videos are fetched from the same source.
+    /// The results of the videos are named by their position in the playlist, as titles may repeat, e.g. "Exercise".
+    pub fn new<S: PlaylistSource + VideoSource + 'static>(source: Arc<S>, playlist: &str, config: Arc<Config>) -> Result<Self, ExtractionError> {
+        let playlist = source.resolve_playlist(playlist)?;
+        let videos = playlist.entries.iter()
+            .enumerate()
+            .map(|(idx, entry)| {
+                let mut analyzer = VideoAnalyzer::from_metadata(source.clone(), entry, config.clone());
+                analyzer.name = Some(format!("{} {:03} {}", playlist.title, idx + 1, entry.title));
+                analyzer
+            })
+            .collect();
+
+        Ok(Self {
+            title: playlist.title,
+            url: playlist.url,
+            videos,
+        })
+    }
+
+    /// Runs the analysis for every video of the playlist, a failing video is logged and its error recorded
+    pub fn run(&mut self) -> Result<(), ExtractionError> {
+        for analyzer in self.videos.iter_mut() {
+            println!("Analyzing {}", analyzer.video.url);
+
+            if let Err(err) = analyzer.run().and_then(|_| analyzer.save_result()) {
+                eprintln!("Error analyzing {}: {}", analyzer.video.url, err);
+                analyzer.error = Some(err.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the aggregated course file of all videos
+    pub fn save_result(&self) -> Result<String, ExtractionError> {
+        let serialized = serde_json::to_string_pretty(&self).map_err(|err| ExtractionError::Io(err.into()))?;
+        let file = format!("./output/{}.json", file_name(&self.title));
+        create_dir_all("./output")?;
+        write(&file, serialized)?;
+
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTING: &str = r#"{
+        "title": "C course",
+        "url": "https://example.com/playlist",
+        "entries": [
+            {"title": "Pointers", "url": "https://example.com/1", "path": "pointers.mp4", "tags": ["c"]},
+            {"title": "Structs", "url": "https://example.com/2"}
+        ]
+    }"#;
+
+    fn write_listing(name: &str, listing: &str) -> PathBuf {
+        let dir = test_dir("playlist", name);
+        write_files(&dir, &[("playlist.json", listing)]);
+        dir.join("playlist.json")
+    }
+
+    #[test]
+    fn expands_fixture_listing() {
+        let path = write_listing("expand", LISTING);
+        let fixture = Arc::new(Fixture::new(&path.display().to_string()));
+        let playlist = PlaylistAnalyzer::new(fixture, "https://example.com/playlist", Arc::new(Config::default())).unwrap();
+
+        assert_eq!(playlist.title, "C course");
+        assert_eq!(playlist.url, "https://example.com/playlist");
+        let titles: Vec<_> = playlist.videos.iter().map(|analyzer| analyzer.video.title.as_str()).collect();
+        assert_eq!(titles, vec!["Pointers", "Structs"]);
+        assert_eq!(playlist.videos[0].video.url, "https://example.com/1");
+        assert_eq!(playlist.videos[0].video.tags, vec!["c"]);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn videos_of_equal_titles_get_distinct_names() {
+        let listing = r#"{
+            "title": "C course",
+            "url": "https://example.com/playlist",
+            "entries": [
+                {"title": "Exercise", "url": "https://example.com/1"},
+                {"title": "Exercise", "url": "https://example.com/2"}
+            ]
+        }"#;
+        let path = write_listing("names", listing);
+        let fixture = Arc::new(Fixture::new(&path.display().to_string()));
+        let playlist = PlaylistAnalyzer::new(fixture, "https://example.com/playlist", Arc::new(Config::default())).unwrap();
+
+        let names: Vec<_> = playlist.videos.iter().map(|analyzer| analyzer.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("C course 001 Exercise"), Some("C course 002 Exercise")]);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[cfg(feature = "video")]
+    #[test]
+    fn records_error_of_failing_video() {
+        let path = write_listing("error", LISTING);
+        let fixture = Arc::new(Fixture::new(&path.display().to_string()));
+        let mut playlist = PlaylistAnalyzer::new(fixture, "https://example.com/playlist", Arc::new(Config::default())).unwrap();
+
+        // the media of the first video does not exist and the second has none
+        playlist.run().unwrap();
+        let errors: Vec<_> = playlist.videos.iter().map(|analyzer| analyzer.error.clone().unwrap_or_default()).collect();
+        assert!(errors[0].starts_with("download failed: fixture media"), "{}", errors[0]);
+        assert!(errors[1].starts_with("download failed: fixture") && errors[1].contains("has no media"), "{}", errors[1]);
+
+        let serialized = serde_json::to_value(&playlist).unwrap();
+        assert!(serialized["videos"][1]["error"].is_string());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
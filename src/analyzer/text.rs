@@ -0,0 +1,168 @@
+use crate::prelude::*;
+
+/// Analyzes source code files directly, skipping any video processing, the result has the shape of a video result
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TextAnalyzer {
+    pub video: Video,
+    /// language of the whole text as a single share
+    pub language: Vec<LanguageShare>,
+    #[serde(default)]
+    pub status: Status,
+    /// confidence, runner-ups and origin of the language
+    #[serde(default, rename = "languageClassification", skip_serializing_if = "Option::is_none")]
+    pub classification: Option<Classification>,
+    /// knowledge components keyed by the lowercase language
+    #[serde(rename = "knowledgeComponents")]
+    pub knowledge_components: IndexMap<String, IndexSet<KnowledgeComponent>>,
+    #[serde(skip)]
+    pub text: String,
+    /// name of the output file, the title unless files of the same name are analyzed together
+    #[serde(skip)]
+    pub name: Option<String>,
+}
+
+impl TextAnalyzer {
+    pub fn from_file(file: &str) -> Result<Self, ExtractionError> {
+        let path = std::fs::canonicalize(file)?.display().to_string();
+        let title = Path::new(&path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.clone());
+        let text = read_to_string(&path)?;
+
+        Ok(Self::new(&title, &path, text))
+    }
+
+    pub fn from_stdin() -> Result<Self, ExtractionError> {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+
+        Ok(Self::new("stdin", "stdin", text))
+    }
+
+    fn new(title: &str, path: &str, text: String) -> Self {
+        Self {
+            video: Video {
+                title: title.into(),
+                url: path.into(),
+                path: path.into(),
+                local: true,
+                description: String::new(),
+                tags: Vec::new(),
+            },
+            language: Vec::new(),
+            status: Status::default(),
+            classification: None,
+            knowledge_components: IndexMap::new(),
+            text,
+            name: None,
+        }
+    }
+
+    /// Names the results of files of the same name by their paths relative to the common directory of those files,
+    /// e.g. `a_main.c` and `b_main.c` for `a/main.c` and `b/main.c`, so they do not overwrite each other
+    pub fn name_apart(analyzers: &mut [TextAnalyzer]) {
+        let mut titles: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for analyzer in analyzers.iter() {
+            titles.entry(analyzer.video.title.clone()).or_default().push(PathBuf::from(&analyzer.video.path));
+        }
+
+        for analyzer in analyzers.iter_mut() {
+            let paths = &titles[&analyzer.video.title];
+            if paths.len() < 2 {
+                continue;
+            }
+            let mut dir = paths[0].parent().unwrap_or_else(|| Path::new(""));
+            while !paths.iter().all(|path| path.starts_with(dir)) {
+                dir = dir.parent().unwrap_or_else(|| Path::new(""));
+            }
+            let name = relative_name(dir, Path::new(&analyzer.video.path));
+            analyzer.name = Some(name).filter(|name| !name.is_empty());
+        }
+    }
+
+    /// Parses the source code line by line, the language is classified if not given explicitly
+    pub fn run(&mut self, language: Option<ProgrammingLanguage>) -> Result<(), ExtractionError> {
+        let classification = language
+            .map(|language| Classification::certain(language, ClassificationSource::Argument))
+            .or_else(|| LanguageClassifier::classify_extension(&self.video.path)
+                .map(|language| Classification::certain(language, ClassificationSource::Extension)))
+            .or_else(|| LanguageClassifier::classify(&self.video.title))
+            .or_else(|| LanguageClassifier::classify_ml(&self.text));
+
+        let mut parser = ProtoParser::new();
+        if let Some(classification) = classification {
+            parser.parse_language(&Locator::Line(self.video.path.clone()), classification);
+        }
+
+        for (idx, line) in self.text.lines().enumerate() {
+            parser.parse(line, idx as i32 + 1, None)?;
+        }
+
+        let (language, status) = classification_result(&parser, &self.video.title);
+        self.language = language;
+        self.status = status;
+        self.classification = parser.classification.clone();
+        self.knowledge_components = IndexMap::new();
+        if let Some(language) = parser.language.as_ref() {
+            self.knowledge_components.insert(language.to_string().to_lowercase(), parser.get_knowledge_components());
+        }
+
+        Ok(())
+    }
+
+    /// Writes the result to the output directory and returns the path of the written file
+    pub fn save_result(&self) -> Result<String, ExtractionError> {
+        let serialized = serde_json::to_string_pretty(&self).map_err(|err| ExtractionError::Io(err.into()))?;
+        let file = format!("./output/{}.json", file_name(self.name.as_ref().unwrap_or(&self.video.title)));
+        create_dir_all("./output")?;
+        write(&file, serialized)?;
+
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_has_the_shape_of_a_video_result() {
+        let text = "#include <stdio.h>\nint main() {\n    printf(\"Hello\");\n    return 0;\n}\n";
+        let mut analyzer = TextAnalyzer::new("hello", "hello", text.into());
+        analyzer.run(Some(ProgrammingLanguage::C)).unwrap();
+
+        let json = serde_json::to_value(&analyzer).unwrap();
+        let shares = json["language"].as_array().unwrap();
+        assert_eq!(shares.len(), 1);
+        assert_eq!(shares[0]["share"], 1.);
+        assert_eq!(shares[0]["source"], "argument");
+
+        let knowledge_components = json["knowledgeComponents"].as_object().unwrap();
+        assert_eq!(knowledge_components.keys().collect::<Vec<_>>(), vec!["c"]);
+        assert!(!knowledge_components["c"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn unclassified_text_has_no_language() {
+        let mut analyzer = TextAnalyzer::new("notes", "notes", String::new());
+        analyzer.run(None).unwrap();
+
+        assert_eq!(analyzer.status, Status::Unclassified);
+        assert!(analyzer.language.is_empty());
+        assert!(analyzer.knowledge_components.is_empty());
+    }
+
+    #[test]
+    fn files_of_the_same_name_are_named_apart() {
+        let mut analyzers = vec![
+            TextAnalyzer::new("main.c", "/src/a/main.c", String::new()),
+            TextAnalyzer::new("main.c", "/src/b/main.c", String::new()),
+            TextAnalyzer::new("util.c", "/src/a/util.c", String::new()),
+        ];
+        TextAnalyzer::name_apart(&mut analyzers);
+
+        let names: Vec<_> = analyzers.iter().map(|analyzer| analyzer.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("a_main.c"), Some("b_main.c"), None]);
+    }
+}
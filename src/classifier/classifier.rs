@@ -3,7 +3,37 @@ use crate::prelude::*;
 // TODO: change to LanguageClassifier(ProgrammingLanguage) and return language to parser
 pub struct LanguageClassifier;
 
-#[derive(Debug, Clone, Copy)]
+/// Names of the languages in metadata, version suffixes like in `python3` or `c++17` are stripped before the lookup.
+/// Other languages like `c#` or `javascript` are words of their own and never match.
+const LANGUAGE_ALIASES: [(&str, ProgrammingLanguage); 14] = [
+    ("c++", ProgrammingLanguage::Cpp),
+    ("cpp", ProgrammingLanguage::Cpp),
+    ("cplusplus", ProgrammingLanguage::Cpp),
+    ("cxx", ProgrammingLanguage::Cpp),
+    ("java", ProgrammingLanguage::Java),
+    ("jdk", ProgrammingLanguage::Java),
+    ("openjdk", ProgrammingLanguage::Java),
+    ("jre", ProgrammingLanguage::Java),
+    ("python", ProgrammingLanguage::Python),
+    ("py", ProgrammingLanguage::Python),
+    ("cpython", ProgrammingLanguage::Python),
+    ("pypy", ProgrammingLanguage::Python),
+    ("c", ProgrammingLanguage::C),
+    ("ansic", ProgrammingLanguage::C),
+];
+
+/// Words next to a lone letter that make it a language, e.g. "C programming" but not "Vitamin C" or "part c"
+const LETTER_CONTEXT: [&str; 8] = ["programming", "programmer", "language", "languages", "code", "coding", "compiler", "tutorial"];
+
+/// Weights of the metadata fields, descriptions often mention related languages as well
+const TITLE_WEIGHT: f64 = 3.;
+#[cfg(any(feature = "video", test))]
+const TAGS_WEIGHT: f64 = 2.;
+#[cfg(any(feature = "video", test))]
+const DESCRIPTION_WEIGHT: f64 = 1.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 #[non_exhaustive]
 pub enum ProgrammingLanguage {
     C,
@@ -24,35 +54,414 @@ impl From<&ProgrammingLanguage> for String {
     }
 }
 
+impl ProgrammingLanguage {
+    /// Returns the file extension of source files of the language
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ProgrammingLanguage::C => "c",
+            ProgrammingLanguage::Cpp => "cpp",
+            ProgrammingLanguage::Java => "java",
+            ProgrammingLanguage::Python => "py",
+        }
+    }
+}
+
+impl std::str::FromStr for ProgrammingLanguage {
+    type Err = ExtractionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "c" => Ok(ProgrammingLanguage::C),
+            "cpp" | "c++" => Ok(ProgrammingLanguage::Cpp),
+            "java" => Ok(ProgrammingLanguage::Java),
+            "python" => Ok(ProgrammingLanguage::Python),
+            _ => Err(ExtractionError::Classification(format!("unsupported programming language '{}'", s))),
+        }
+    }
+}
+
+/// Where the language of a classification was taken from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassificationSource {
+    /// given on the command line
+    Argument,
+    /// file extension of the source file
+    Extension,
+    /// title, tags or description of the video
+    Metadata,
+    /// recognized source code
+    Code,
+}
+
+/// Chosen language along with its probability and the ranked runner-ups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Classification {
+    pub language: ProgrammingLanguage,
+    pub confidence: f64,
+    pub source: ClassificationSource,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub runner_ups: Vec<Candidate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candidate {
+    pub language: ProgrammingLanguage,
+    pub probability: f64,
+}
+
+impl Classification {
+    /// Chooses the first language of a ranking, languages without probability are no runner-ups
+    pub fn from_ranking(ranking: Vec<(ProgrammingLanguage, f64)>, source: ClassificationSource) -> Option<Self> {
+        let mut ranking = ranking.into_iter();
+        let (language, confidence) = ranking.next()?;
+
+        Some(Self {
+            language,
+            confidence,
+            source,
+            runner_ups: ranking
+                .filter(|(_, probability)| *probability > 0.)
+                .map(|(language, probability)| Candidate { language, probability })
+                .collect(),
+        })
+    }
+
+    /// Returns the probabilities of the chosen language and the runner-ups
+    #[cfg(any(feature = "video", test))]
+    pub fn probabilities(&self) -> Vec<(ProgrammingLanguage, f64)> {
+        std::iter::once((self.language, self.confidence))
+            .chain(self.runner_ups.iter().map(|candidate| (candidate.language, candidate.probability)))
+            .collect()
+    }
+
+    /// Classification without doubt, e.g. given explicitly
+    pub fn certain(language: ProgrammingLanguage, source: ClassificationSource) -> Self {
+        Self {
+            language,
+            confidence: 1.,
+            source,
+            runner_ups: Vec::new(),
+        }
+    }
+}
+
+/// Votes on the language of a video or region by the classifications of its texts, every text adds its probabilities
+#[cfg(any(feature = "video", test))]
+#[derive(Debug)]
+pub struct LanguageVote {
+    config: ClassificationConfig,
+    votes: HashMap<ProgrammingLanguage, f64>,
+    count: usize,
+    /// votes of the languages named in the metadata, they rank the result but do not count as texts
+    prior: HashMap<ProgrammingLanguage, f64>,
+}
+
+#[cfg(any(feature = "video", test))]
+impl LanguageVote {
+    pub fn new(config: &ClassificationConfig) -> Self {
+        Self {
+            config: config.clone(),
+            votes: HashMap::new(),
+            count: 0,
+            prior: HashMap::new(),
+        }
+    }
+
+    /// Adds `metadataVotes` votes for the languages named in the metadata to the result,
+    /// so they tip an uncertain vote, while the texts still outvote a wrong or unrelated title
+    pub fn with_prior(mut self, metadata: Option<&Classification>) -> Self {
+        if let Some(metadata) = metadata {
+            for (language, probability) in metadata.probabilities() {
+                *self.prior.entry(language).or_default() += probability * self.config.metadata_votes;
+            }
+        }
+        self
+    }
+
+    /// Adds the vote of the text, texts shorter than the threshold are too noisy to vote
+    pub fn add(&mut self, text: &str) {
+        if text.chars().filter(|c| !c.is_whitespace()).count() < self.config.threshold {
+            return;
+        }
+        let classification = match LanguageClassifier::classify_ml(text) {
+            Some(classification) => classification,
+            None => return,
+        };
+
+        for (language, probability) in classification.probabilities() {
+            *self.votes.entry(language).or_default() += probability;
+        }
+        self.count += 1;
+    }
+
+    /// Returns the result once enough texts voted and their leading language holds the configured share of their votes
+    /// and outweighs the metadata, or once the maximal number of texts voted
+    pub fn settled(&self) -> Option<Classification> {
+        let result = self.result()?;
+        let confident = self.votes.get(&result.language)
+            .is_some_and(|votes| votes / self.count as f64 >= self.config.confidence);
+        let settled = self.count >= self.config.max_votes || (self.count >= self.config.min_votes && confident);
+
+        Some(result).filter(|_| settled)
+    }
+
+    /// Share of the votes of every language including the metadata, the confidence is the share of the leading language.
+    /// Without any text the result is the language named in the metadata, if any.
+    pub fn result(&self) -> Option<Classification> {
+        let mut votes = self.votes.clone();
+        for (language, prior) in self.prior.iter() {
+            *votes.entry(*language).or_default() += prior;
+        }
+        let total: f64 = votes.values().sum();
+        if total <= 0. {
+            return None;
+        }
+        let mut ranking: Vec<_> = votes.into_iter()
+            .map(|(language, votes)| (language, votes / total))
+            .collect();
+        ranking.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        let source = if self.count > 0 { ClassificationSource::Code } else { ClassificationSource::Metadata };
+        Classification::from_ranking(ranking, source)
+    }
+}
+
 impl LanguageClassifier {
-    pub fn classify<S: Into<String>>(val: S) -> Option<ProgrammingLanguage> {
-        let lang = val.into().to_lowercase();
+    /// Classifies by the languages named in a title, several named languages share the probability
+    pub fn classify<S: Into<String>>(val: S) -> Option<Classification> {
+        let mut weights = Vec::new();
+        add_named_languages(&mut weights, &val.into(), TITLE_WEIGHT);
+
+        rank_named_languages(weights)
+    }
+
+    /// Classifies by the languages named in title, tags and description of a video, weighted by the field they are named in.
+    /// A tag may name a language by a single letter, e.g. `c`, in title and description the letter needs a context like "C programming".
+    #[cfg(any(feature = "video", test))]
+    pub fn classify_metadata(metadata: &VideoMetadata) -> Option<Classification> {
+        let mut weights = Vec::new();
+        add_named_languages(&mut weights, &metadata.title, TITLE_WEIGHT);
+        add_named_tags(&mut weights, &metadata.tags, TAGS_WEIGHT);
+        add_named_languages(&mut weights, &metadata.description, DESCRIPTION_WEIGHT);
+
+        rank_named_languages(weights)
+    }
 
-        if lang.contains("c++") || lang.contains("cpp") {
-            return Some(ProgrammingLanguage::Cpp);
-        } else if lang.contains("java") {
-            return Some(ProgrammingLanguage::Java);
-        } else if lang.contains("python") {
-            return Some(ProgrammingLanguage::Python);
-        } else if lang.contains("c ") {
-            return Some(ProgrammingLanguage::C);
+    pub fn classify_extension(path: &str) -> Option<ProgrammingLanguage> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "c" | "h" => Some(ProgrammingLanguage::C),
+            "cpp" | "cc" | "cxx" | "hpp" | "hh" => Some(ProgrammingLanguage::Cpp),
+            "java" => Some(ProgrammingLanguage::Java),
+            "py" => Some(ProgrammingLanguage::Python),
+            _ => None,
         }
+    }
 
-        return None
+    /// Classifies source code by the naive Bayes model trained on the shipped sample programs
+    pub fn classify_ml(val: &str) -> Option<Classification> {
+        Classification::from_ranking(NaiveBayes::default_model().predict(val), ClassificationSource::Code)
     }
+}
 
-    pub fn classify_ml(val: &str) -> Option<ProgrammingLanguage> {
-        let output = Command::new("./src/classifier/classifier").args(&[&format!("{}", val)]).output().unwrap();
-        let language = from_utf8(&output.stdout).unwrap();
+/// Adds the weight to every language named by a word of the text, a language named several times counts once
+fn add_named_languages(weights: &mut Vec<(ProgrammingLanguage, f64)>, text: &str, weight: f64) {
+    add_weight(weights, named_languages(text), weight);
+}
 
-        if "c_cpp" == language {
-            return Some(ProgrammingLanguage::Cpp);
-        }else if "java" == language {
-            return Some (ProgrammingLanguage::Java);
-        } else if "python" == language {
-            return Some(ProgrammingLanguage::Python);
+/// Adds the weight to every language named by a tag, a tag of its own may name a language by a single letter
+#[cfg(any(feature = "video", test))]
+fn add_named_tags(weights: &mut Vec<(ProgrammingLanguage, f64)>, tags: &[String], weight: f64) {
+    let mut named = Vec::new();
+
+    for tag in tags.iter() {
+        let languages = match alias(normalize(&tag.trim().to_lowercase())) {
+            Some(language) => vec![language],
+            None => named_languages(tag),
+        };
+        for language in languages {
+            if !named.contains(&language) {
+                named.push(language);
+            }
         }
+    }
+
+    add_weight(weights, named, weight);
+}
+
+/// Languages named by the words of the text in the order they are named.
+/// Hyphenated words stay whole, so `objective-c` names no language, but a part of at least two letters does, e.g. `python-tutorial`.
+/// A lone letter only names a language next to a word like "programming" or another language, e.g. "C/C++".
+fn named_languages(text: &str) -> Vec<ProgrammingLanguage> {
+    let text = text.to_lowercase();
+    let words: Vec<&str> = text.split(|c: char| !(c.is_alphanumeric() || "+#-".contains(c)))
+        .map(normalize)
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mut named = Vec::new();
 
-        None
+    for (idx, word) in words.iter().enumerate() {
+        let language = if word.contains('-') {
+            word.split('-').filter(|part| part.chars().count() >= 2).find_map(alias)
+        } else if word.chars().count() < 2 {
+            let context = idx.checked_sub(1).and_then(|previous| words.get(previous)).into_iter().chain(words.get(idx + 1))
+                .any(|neighbor| LETTER_CONTEXT.contains(neighbor) || (neighbor.chars().count() >= 2 && alias(neighbor).is_some()));
+            alias(word).filter(|_| context)
+        } else {
+            alias(word)
+        };
+
+        if let Some(language) = language {
+            if !named.contains(&language) {
+                named.push(language);
+            }
+        }
     }
-}
\ No newline at end of file
+
+    named
+}
+
+/// Strips hashtags, which name the language as well, versions and surrounding hyphens
+fn normalize(word: &str) -> &str {
+    word.trim_matches('-')
+        .trim_start_matches('#')
+        .trim_end_matches(|c: char| c.is_ascii_digit())
+        .trim_end_matches('-')
+}
+
+fn alias(word: &str) -> Option<ProgrammingLanguage> {
+    LANGUAGE_ALIASES.iter().find(|(alias, _)| *alias == word).map(|(_, language)| *language)
+}
+
+fn add_weight(weights: &mut Vec<(ProgrammingLanguage, f64)>, named: Vec<ProgrammingLanguage>, weight: f64) {
+    for language in named {
+        match weights.iter_mut().find(|(named, _)| *named == language) {
+            Some((_, total)) => *total += weight,
+            None => weights.push((language, weight)),
+        }
+    }
+}
+
+/// Ranks the named languages by their share of the weights, languages named equally keep the order they were named in
+fn rank_named_languages(mut weights: Vec<(ProgrammingLanguage, f64)>) -> Option<Classification> {
+    let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+    weights.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let ranking = weights.into_iter().map(|(language, weight)| (language, weight / total)).collect();
+    Classification::from_ranking(ranking, ClassificationSource::Metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ProgrammingLanguage::*;
+
+    const C_PROGRAM: &str = "#include <stdio.h>\nint main(void) {\n    printf(\"%d\\n\", 1);\n    return 0;\n}";
+    /// valid C and C++, the model leans to C++ without being certain
+    const AMBIGUOUS: &str = "int main() { return 0; }";
+
+    fn vote(min_votes: usize, max_votes: usize) -> LanguageVote {
+        LanguageVote::new(&ClassificationConfig { min_votes, max_votes, ..ClassificationConfig::default() })
+    }
+
+    #[test]
+    fn vote_settles_after_min_votes_with_confidence() {
+        let mut vote = vote(3, 30);
+        vote.add(C_PROGRAM);
+        vote.add(C_PROGRAM);
+        assert!(vote.settled().is_none());
+        assert_eq!(vote.result().unwrap().language, C);
+
+        vote.add(C_PROGRAM);
+        let classification = vote.settled().unwrap();
+        assert_eq!(classification.language, C);
+        assert!(classification.confidence >= 0.75);
+        assert_eq!(classification.source, ClassificationSource::Code);
+    }
+
+    #[test]
+    fn uncertain_vote_settles_at_max_votes() {
+        let mut vote = vote(2, 4);
+        for _ in 0..3 {
+            vote.add(AMBIGUOUS);
+        }
+        assert!(vote.settled().is_none());
+
+        vote.add(AMBIGUOUS);
+        let classification = vote.settled().unwrap();
+        assert_eq!(classification.language, Cpp);
+        assert!(classification.confidence < 0.75);
+        assert_eq!(classification.runner_ups[0].language, C);
+    }
+
+    #[test]
+    fn short_texts_do_not_vote() {
+        let mut vote = vote(1, 30);
+        vote.add("x = 1;");
+        vote.add("   }   ");
+        assert!(vote.result().is_none());
+    }
+
+    #[test]
+    fn metadata_tips_an_uncertain_vote() {
+        let metadata = Classification::certain(C, ClassificationSource::Metadata);
+        let mut vote = vote(1, 30).with_prior(Some(&metadata));
+        vote.add(AMBIGUOUS);
+
+        let classification = vote.result().unwrap();
+        assert_eq!(classification.language, C);
+        assert_eq!(classification.source, ClassificationSource::Code);
+    }
+
+    #[test]
+    fn code_outvotes_the_metadata() {
+        let metadata = Classification::certain(Python, ClassificationSource::Metadata);
+        let mut vote = vote(3, 30).with_prior(Some(&metadata));
+        for _ in 0..4 {
+            vote.add(C_PROGRAM);
+        }
+        let classification = vote.settled().unwrap();
+        assert_eq!(classification.language, C);
+        assert_eq!(classification.runner_ups[0].language, Python);
+    }
+
+    #[test]
+    fn metadata_alone_does_not_settle() {
+        let metadata = Classification::certain(Python, ClassificationSource::Metadata);
+        let vote = vote(1, 30).with_prior(Some(&metadata));
+
+        assert!(vote.settled().is_none());
+        let classification = vote.result().unwrap();
+        assert_eq!(classification.language, Python);
+        assert_eq!(classification.source, ClassificationSource::Metadata);
+    }
+
+    fn named(title: &str, tags: &[&str]) -> Option<ProgrammingLanguage> {
+        let mut metadata = VideoMetadata::new(title, "v.mp4", None);
+        metadata.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        LanguageClassifier::classify_metadata(&metadata).map(|classification| classification.language)
+    }
+
+    #[test]
+    fn lone_letters_do_not_name_c() {
+        assert_eq!(named("Objective-C basics", &[]), None);
+        assert_eq!(named("Vitamin C and you", &[]), None);
+        assert_eq!(named("Plan A, B and C", &[]), None);
+        assert_eq!(named("Lecture 3 part c", &[]), None);
+        assert_eq!(named("Lecture 3", &["part c", "objective-c"]), None);
+    }
+
+    #[test]
+    fn languages_are_named_in_context() {
+        assert_eq!(named("C programming tutorial", &[]), Some(C));
+        assert_eq!(named("Learn the C language", &[]), Some(C));
+        assert_eq!(named("Pointers explained", &["c"]), Some(C));
+        assert_eq!(named("python3 intro", &[]), Some(Python));
+        assert_eq!(named("#java tips", &[]), Some(Java));
+        assert_eq!(named("Lambdas", &["python-tutorial"]), Some(Python));
+        assert_eq!(named("C++17 lambdas", &[]), Some(Cpp));
+    }
+}
@@ -1 +1,2 @@
-pub mod classifier;
\ No newline at end of file
+pub mod classifier;
+pub mod naive_bayes;
@@ -0,0 +1,235 @@
+use crate::prelude::*;
+
+/// Sample programs the default model is trained on, the language of a sample is given by its extension
+const SAMPLES: [(&str, &str); 12] = [
+    ("linked_list.c", include_str!("samples/linked_list.c")),
+    ("strings.c", include_str!("samples/strings.c")),
+    ("sort.c", include_str!("samples/sort.c")),
+    ("shapes.cpp", include_str!("samples/shapes.cpp")),
+    ("templates.cpp", include_str!("samples/templates.cpp")),
+    ("bank.cpp", include_str!("samples/bank.cpp")),
+    ("Shapes.java", include_str!("samples/Shapes.java")),
+    ("Inventory.java", include_str!("samples/Inventory.java")),
+    ("Threads.java", include_str!("samples/Threads.java")),
+    ("shapes.py", include_str!("samples/shapes.py")),
+    ("words.py", include_str!("samples/words.py")),
+    ("numbers.py", include_str!("samples/numbers.py")),
+];
+
+/// Weight of the evidence of a text, see `NaiveBayes::predict`
+const EVIDENCE_WEIGHT: f64 = 0.5;
+
+/// Multinomial naive Bayes over the tokens, token bigrams and character trigrams of source code
+#[derive(Debug, Default)]
+pub struct NaiveBayes {
+    languages: Vec<LanguageModel>,
+    vocabulary: HashSet<String>,
+    documents: usize,
+}
+
+#[derive(Debug)]
+struct LanguageModel {
+    language: ProgrammingLanguage,
+    documents: usize,
+    counts: HashMap<String, usize>,
+    total: usize,
+}
+
+impl NaiveBayes {
+    /// Returns the model trained on the sample programs shipped with the crate, it is trained on first use
+    pub fn default_model() -> &'static NaiveBayes {
+        static MODEL: OnceLock<NaiveBayes> = OnceLock::new();
+
+        MODEL.get_or_init(|| {
+            let mut model = NaiveBayes::default();
+            for (name, program) in SAMPLES.iter() {
+                if let Some(language) = LanguageClassifier::classify_extension(name) {
+                    model.train(language, program);
+                }
+            }
+            model
+        })
+    }
+
+    /// Adds a program of the language to the model
+    pub fn train(&mut self, language: ProgrammingLanguage, program: &str) {
+        let idx = match self.languages.iter().position(|model| model.language == language) {
+            Some(idx) => idx,
+            None => {
+                self.languages.push(LanguageModel {
+                    language,
+                    documents: 0,
+                    counts: HashMap::new(),
+                    total: 0,
+                });
+                self.languages.len() - 1
+            }
+        };
+        let model = &mut self.languages[idx];
+
+        for feature in features(program) {
+            *model.counts.entry(feature.clone()).or_default() += 1;
+            model.total += 1;
+            self.vocabulary.insert(feature);
+        }
+        model.documents += 1;
+        self.documents += 1;
+    }
+
+    /// Returns the probability of every trained language, the most probable first.
+    /// The result is empty if the text has no features, e.g. if it is empty.
+    pub fn predict(&self, text: &str) -> Vec<(ProgrammingLanguage, f64)> {
+        let features = features(text);
+        if features.is_empty() || self.languages.is_empty() {
+            return Vec::new();
+        }
+
+        // log likelihoods with add one smoothing, features unknown to every language say nothing and are skipped.
+        // The features overlap, a token is also part of bigrams and trigrams, so the plain posteriors are overconfident,
+        // the likelihood is tempered so the evidence only grows with the square root of the number of features.
+        let vocabulary = self.vocabulary.len() as f64;
+        let temper = EVIDENCE_WEIGHT / (features.len() as f64).sqrt();
+        let scores: Vec<(ProgrammingLanguage, f64)> = self.languages.iter()
+            .map(|model| {
+                let prior = (model.documents as f64 / self.documents as f64).ln();
+                let likelihood: f64 = features.iter()
+                    .filter(|feature| self.vocabulary.contains(*feature))
+                    .map(|feature| {
+                        let count = model.counts.get(feature).copied().unwrap_or(0) as f64;
+                        ((count + 1.) / (model.total as f64 + vocabulary)).ln()
+                    })
+                    .sum();
+                (model.language, prior + temper * likelihood)
+            })
+            .collect();
+
+        // normalize the log scores to probabilities, shifted by the maximum to avoid underflow
+        let max = scores.iter().map(|(_, score)| *score).fold(f64::NEG_INFINITY, f64::max);
+        let sum: f64 = scores.iter().map(|(_, score)| (score - max).exp()).sum();
+        let mut probabilities: Vec<_> = scores.into_iter()
+            .map(|(language, score)| (language, (score - max).exp() / sum))
+            .collect();
+        probabilities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        probabilities
+    }
+}
+
+/// Tokens, token bigrams and character trigrams of the text
+fn features(text: &str) -> Vec<String> {
+    let tokens = tokenize(text);
+    let mut features: Vec<String> = tokens.iter().map(|token| format!("t:{}", token)).collect();
+
+    features.extend(tokens.windows(2).map(|pair| format!("b:{} {}", pair[0], pair[1])));
+
+    // runs of spaces are collapsed, as the indentation width says nothing about the language
+    let mut chars: Vec<char> = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        let c = if c == '\t' { ' ' } else { c };
+        if c == ' ' && chars.last() == Some(&' ') {
+            continue;
+        }
+        chars.push(c);
+    }
+    features.extend(chars.windows(3)
+        .filter(|trigram| trigram.iter().any(|c| !c.is_whitespace()))
+        .map(|trigram| format!("c:{}", trigram.iter().collect::<String>())));
+
+    features
+}
+
+/// Splits the text into words and operators, numbers and the contents of string literals are replaced by placeholders
+fn tokenize(text: &str) -> Vec<String> {
+    const OPERATORS: &str = "+-*/%=<>!&|^~:.?";
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let c = chars[idx];
+        let start = idx;
+        idx += 1;
+
+        if c.is_alphabetic() || c == '_' {
+            while idx < chars.len() && (chars[idx].is_alphanumeric() || chars[idx] == '_') {
+                idx += 1;
+            }
+            tokens.push(chars[start..idx].iter().collect());
+        } else if c.is_ascii_digit() {
+            while idx < chars.len() && (chars[idx].is_alphanumeric() || chars[idx] == '.') {
+                idx += 1;
+            }
+            tokens.push("<num>".into());
+        } else if c == '"' || c == '\'' {
+            // a literal ends at the closing quote or at the end of the line, as OCR may lose the quote
+            while idx < chars.len() && chars[idx] != c && chars[idx] != '\n' {
+                idx += if chars[idx] == '\\' { 2 } else { 1 };
+            }
+            idx = (idx + 1).min(chars.len());
+            tokens.push(format!("{}<str>{}", c, c));
+        } else if OPERATORS.contains(c) {
+            while idx < chars.len() && OPERATORS.contains(chars[idx]) {
+                idx += 1;
+            }
+            tokens.push(chars[start..idx].iter().collect());
+        } else if !c.is_whitespace() {
+            tokens.push(c.to_string());
+        }
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ProgrammingLanguage::*;
+
+    fn leading(text: &str) -> (ProgrammingLanguage, f64) {
+        NaiveBayes::default_model().predict(text)[0]
+    }
+
+    fn top_two(text: &str) -> HashSet<ProgrammingLanguage> {
+        NaiveBayes::default_model().predict(text).iter().take(2).map(|(language, _)| *language).collect()
+    }
+
+    #[test]
+    fn classifies_distinctive_programs() {
+        let programs = [
+            (C, "#include <stdio.h>\nint main(void) {\n    printf(\"%d\\n\", 1);\n    return 0;\n}"),
+            (C, "char *name = malloc(10);"),
+            (Cpp, "#include <iostream>\nint main() {\n    std::cout << \"hi\" << std::endl;\n}"),
+            (Cpp, "std::vector<int> values;"),
+            (Java, "public class Main {\n    public static void main(String[] args) {\n        System.out.println(\"hi\");\n    }\n}"),
+            (Java, "List<String> names = new ArrayList<>();"),
+            (Python, "def main():\n    print('hi')\n\nif __name__ == '__main__':\n    main()"),
+            (Python, "for i in range(10):\n    print(i)"),
+        ];
+
+        for (language, program) in programs.iter() {
+            let (predicted, probability) = leading(program);
+            assert_eq!(predicted, *language, "{}", program);
+            assert!(probability > 0.6, "{} {}", program, probability);
+        }
+    }
+
+    #[test]
+    fn ambiguous_programs_stay_uncertain() {
+        // valid in both languages, so neither may be certain, which leaves the decision to the vote across frames
+        for (languages, program) in [([C, Cpp], "int main() { return 0; }"), ([Cpp, Java], "class Foo { }")].iter() {
+            assert_eq!(top_two(program), languages.iter().copied().collect());
+            assert!(leading(program).1 < 0.75, "{}", program);
+        }
+    }
+
+    #[test]
+    fn probabilities_are_ranked_and_normalized() {
+        let probabilities = NaiveBayes::default_model().predict("int x = 1;");
+
+        assert_eq!(probabilities.len(), 4);
+        assert!(probabilities.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert!((probabilities.iter().map(|(_, probability)| probability).sum::<f64>() - 1.).abs() < 1e-9);
+        assert!(NaiveBayes::default_model().predict("").is_empty());
+    }
+}
@@ -0,0 +1,41 @@
+package com.example.inventory;
+
+import java.util.HashMap;
+import java.util.Map;
+import java.util.Scanner;
+
+public class Inventory {
+    private Map<String, Integer> items = new HashMap<String, Integer>();
+
+    public void add(String name, int quantity) {
+        items.put(name, items.getOrDefault(name, 0) + quantity);
+    }
+
+    public boolean remove(String name, int quantity) throws IllegalArgumentException {
+        Integer current = items.get(name);
+        if (current == null) {
+            throw new IllegalArgumentException("unknown item " + name);
+        }
+        if (current < quantity) {
+            return false;
+        }
+        items.put(name, current - quantity);
+        return true;
+    }
+
+    public static void main(String[] args) {
+        Inventory inventory = new Inventory();
+        Scanner scanner = new Scanner(System.in);
+
+        while (scanner.hasNextLine()) {
+            String[] parts = scanner.nextLine().split(" ");
+            int quantity = Integer.parseInt(parts[1]);
+            inventory.add(parts[0], quantity);
+        }
+        scanner.close();
+
+        for (Map.Entry<String, Integer> entry : inventory.items.entrySet()) {
+            System.out.printf("%s: %d%n", entry.getKey(), entry.getValue());
+        }
+    }
+}
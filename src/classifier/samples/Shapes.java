@@ -0,0 +1,54 @@
+import java.util.ArrayList;
+import java.util.List;
+
+public class Shapes {
+
+    interface Shape {
+        double area();
+    }
+
+    static class Circle implements Shape {
+        private final double radius;
+
+        Circle(double radius) {
+            this.radius = radius;
+        }
+
+        @Override
+        public double area() {
+            return Math.PI * radius * radius;
+        }
+    }
+
+    static abstract class Polygon implements Shape {
+        protected int sides;
+
+        public int getSides() {
+            return sides;
+        }
+    }
+
+    static class Square extends Polygon {
+        private double length;
+
+        public Square(double length) {
+            this.length = length;
+            this.sides = 4;
+        }
+
+        @Override
+        public double area() {
+            return length * length;
+        }
+    }
+
+    public static void main(String[] args) {
+        List<Shape> shapes = new ArrayList<>();
+        shapes.add(new Circle(2.0));
+        shapes.add(new Square(3.0));
+
+        for (Shape shape : shapes) {
+            System.out.println(shape.getClass().getSimpleName() + ": " + shape.area());
+        }
+    }
+}
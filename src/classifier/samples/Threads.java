@@ -0,0 +1,39 @@
+import java.util.concurrent.ExecutorService;
+import java.util.concurrent.Executors;
+import java.util.concurrent.TimeUnit;
+
+public final class Threads {
+    private static int counter = 0;
+
+    private static synchronized void increment() {
+        counter++;
+    }
+
+    public static long fibonacci(int n) {
+        if (n < 2) {
+            return n;
+        }
+        long a = 0, b = 1;
+        for (int i = 2; i <= n; i++) {
+            long next = a + b;
+            a = b;
+            b = next;
+        }
+        return b;
+    }
+
+    public static void main(String[] args) throws InterruptedException {
+        ExecutorService executor = Executors.newFixedThreadPool(4);
+
+        for (int i = 0; i < 100; i++) {
+            executor.submit(() -> increment());
+        }
+        executor.shutdown();
+        executor.awaitTermination(1, TimeUnit.MINUTES);
+
+        StringBuilder builder = new StringBuilder();
+        builder.append("counter = ").append(counter);
+        System.out.println(builder.toString());
+        System.out.println("fib(50) = " + fibonacci(50));
+    }
+}
@@ -0,0 +1,48 @@
+#include <iostream>
+#include <stdexcept>
+#include <string>
+
+class Account {
+    std::string owner;
+    double balance;
+
+public:
+    Account(const std::string &owner, double balance = 0.0) : owner(owner), balance(balance) {}
+
+    void deposit(double amount) {
+        if (amount <= 0) {
+            throw std::invalid_argument("amount must be positive");
+        }
+        balance += amount;
+    }
+
+    void withdraw(double amount) {
+        if (amount > balance) {
+            throw std::runtime_error("insufficient funds");
+        }
+        balance -= amount;
+    }
+
+    double getBalance() const { return balance; }
+
+    friend std::ostream &operator<<(std::ostream &os, const Account &account) {
+        return os << account.owner << ": " << account.balance;
+    }
+};
+
+int main() {
+    Account *account = new Account("alice", 100.0);
+    int choice;
+
+    try {
+        account->deposit(50.0);
+        account->withdraw(500.0);
+    } catch (const std::exception &e) {
+        std::cerr << "error: " << e.what() << std::endl;
+    }
+
+    std::cin >> choice;
+    std::cout << *account << std::endl;
+    delete account;
+    return 0;
+}
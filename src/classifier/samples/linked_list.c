@@ -0,0 +1,45 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+struct node {
+    int value;
+    struct node *next;
+};
+
+struct node *push(struct node *head, int value) {
+    struct node *node = malloc(sizeof(struct node));
+    if (node == NULL) {
+        fprintf(stderr, "out of memory\n");
+        exit(EXIT_FAILURE);
+    }
+    node->value = value;
+    node->next = head;
+    return node;
+}
+
+void print_list(const struct node *head) {
+    for (const struct node *it = head; it != NULL; it = it->next) {
+        printf("%d ", it->value);
+    }
+    printf("\n");
+}
+
+void free_list(struct node *head) {
+    while (head != NULL) {
+        struct node *next = head->next;
+        free(head);
+        head = next;
+    }
+}
+
+int main(void) {
+    struct node *head = NULL;
+    int i;
+
+    for (i = 0; i < 10; i++) {
+        head = push(head, i * i);
+    }
+    print_list(head);
+    free_list(head);
+    return 0;
+}
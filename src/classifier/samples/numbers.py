@@ -0,0 +1,42 @@
+def fibonacci(n):
+    a, b = 0, 1
+    for _ in range(n):
+        yield a
+        a, b = b, a + b
+
+
+def is_prime(number):
+    if number < 2:
+        return False
+    for divisor in range(2, int(number ** 0.5) + 1):
+        if number % divisor == 0:
+            return False
+    return True
+
+
+class Matrix:
+    def __init__(self, rows):
+        self.rows = rows
+
+    def transpose(self):
+        return Matrix([list(column) for column in zip(*self.rows)])
+
+    def __repr__(self):
+        return "\n".join(" ".join(str(value) for value in row) for row in self.rows)
+
+
+numbers = [int(value) for value in input("numbers: ").split()]
+primes = [number for number in numbers if is_prime(number)]
+print("primes:", primes)
+print("fibonacci:", list(fibonacci(10)))
+
+matrix = Matrix([[1, 2, 3], [4, 5, 6]])
+print(matrix.transpose())
+
+while True:
+    try:
+        value = int(input())
+    except ValueError:
+        break
+    else:
+        print(value * 2 if value > 0 else None)
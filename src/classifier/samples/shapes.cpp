@@ -0,0 +1,45 @@
+#include <iostream>
+#include <vector>
+#include <memory>
+#include <string>
+
+using namespace std;
+
+class Shape {
+public:
+    virtual ~Shape() = default;
+    virtual double area() const = 0;
+    virtual string name() const { return "shape"; }
+};
+
+class Circle : public Shape {
+public:
+    explicit Circle(double radius) : radius_(radius) {}
+    double area() const override { return 3.14159 * radius_ * radius_; }
+    string name() const override { return "circle"; }
+
+private:
+    double radius_;
+};
+
+class Rectangle : public Shape {
+public:
+    Rectangle(double width, double height) : width_(width), height_(height) {}
+    double area() const override { return width_ * height_; }
+    string name() const override { return "rectangle"; }
+
+private:
+    double width_;
+    double height_;
+};
+
+int main() {
+    vector<unique_ptr<Shape>> shapes;
+    shapes.push_back(make_unique<Circle>(2.0));
+    shapes.push_back(make_unique<Rectangle>(3.0, 4.0));
+
+    for (const auto &shape : shapes) {
+        cout << shape->name() << ": " << shape->area() << endl;
+    }
+    return 0;
+}
@@ -0,0 +1,39 @@
+import math
+from abc import ABC, abstractmethod
+
+
+class Shape(ABC):
+    @abstractmethod
+    def area(self):
+        pass
+
+    def __str__(self):
+        return f"{self.__class__.__name__}: {self.area():.2f}"
+
+
+class Circle(Shape):
+    def __init__(self, radius):
+        self.radius = radius
+
+    def area(self):
+        return math.pi * self.radius ** 2
+
+
+class Rectangle(Shape):
+    def __init__(self, width, height):
+        self.width = width
+        self.height = height
+
+    def area(self):
+        return self.width * self.height
+
+
+def largest(shapes):
+    return max(shapes, key=lambda shape: shape.area())
+
+
+if __name__ == "__main__":
+    shapes = [Circle(2), Rectangle(3, 4)]
+    for shape in shapes:
+        print(shape)
+    print("largest:", largest(shapes))
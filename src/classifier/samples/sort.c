@@ -0,0 +1,50 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+void swap(int *a, int *b) {
+    int tmp = *a;
+    *a = *b;
+    *b = tmp;
+}
+
+void bubble_sort(int arr[], int n) {
+    int i, j;
+    for (i = 0; i < n - 1; i++) {
+        for (j = 0; j < n - i - 1; j++) {
+            if (arr[j] > arr[j + 1]) {
+                swap(&arr[j], &arr[j + 1]);
+            }
+        }
+    }
+}
+
+int compare(const void *a, const void *b) {
+    return (*(int *) a - *(int *) b);
+}
+
+int *read_numbers(int *n) {
+    int *numbers;
+    int i;
+
+    scanf("%d", n);
+    numbers = (int *) calloc(*n, sizeof(int));
+    for (i = 0; i < *n; i++) {
+        scanf("%d", &numbers[i]);
+    }
+    return numbers;
+}
+
+int main() {
+    int n, i;
+    int *numbers = read_numbers(&n);
+    int copy[5] = {5, 3, 1, 4, 2};
+
+    qsort(numbers, n, sizeof(int), compare);
+    bubble_sort(copy, 5);
+
+    for (i = 0; i < n; i++) {
+        printf("%d\n", numbers[i]);
+    }
+    free(numbers);
+    return 0;
+}
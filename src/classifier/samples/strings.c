@@ -0,0 +1,60 @@
+#include <stdio.h>
+#include <string.h>
+#include <ctype.h>
+
+#define MAX_LINE 256
+
+typedef struct {
+    char name[32];
+    int count;
+} word_t;
+
+static int count_words(const char *line) {
+    int count = 0;
+    int in_word = 0;
+
+    while (*line) {
+        if (isspace((unsigned char) *line)) {
+            in_word = 0;
+        } else if (!in_word) {
+            in_word = 1;
+            count++;
+        }
+        line++;
+    }
+    return count;
+}
+
+void reverse(char *str) {
+    size_t len = strlen(str);
+    for (size_t i = 0; i < len / 2; i++) {
+        char tmp = str[i];
+        str[i] = str[len - 1 - i];
+        str[len - 1 - i] = tmp;
+    }
+}
+
+int main(int argc, char *argv[]) {
+    char line[MAX_LINE];
+    FILE *fp;
+    word_t total = { "total", 0 };
+
+    if (argc < 2) {
+        printf("usage: %s <file>\n", argv[0]);
+        return 1;
+    }
+    fp = fopen(argv[1], "r");
+    if (!fp) {
+        perror("fopen");
+        return 1;
+    }
+    while (fgets(line, sizeof(line), fp) != NULL) {
+        total.count += count_words(line);
+    }
+    fclose(fp);
+
+    strcpy(line, "hello world");
+    reverse(line);
+    printf("%s: %d, reversed: %s\n", total.name, total.count, line);
+    return 0;
+}
@@ -0,0 +1,45 @@
+#include <iostream>
+#include <map>
+#include <algorithm>
+#include <vector>
+#include <string>
+
+template <typename T>
+T max_of(const std::vector<T> &values) {
+    T result = values.front();
+    for (const T &value : values) {
+        if (value > result) {
+            result = value;
+        }
+    }
+    return result;
+}
+
+namespace stats {
+    std::map<std::string, int> count_words(const std::vector<std::string> &words) {
+        std::map<std::string, int> counts;
+        for (auto &word : words) {
+            counts[word]++;
+        }
+        return counts;
+    }
+}
+
+int main() {
+    std::vector<int> numbers = {4, 8, 15, 16, 23, 42};
+    std::vector<std::string> words{"a", "b", "a", "c"};
+
+    std::sort(numbers.begin(), numbers.end(), [](int a, int b) { return a > b; });
+    std::cout << "max: " << max_of(numbers) << std::endl;
+
+    auto counts = stats::count_words(words);
+    for (const auto &[word, count] : counts) {
+        std::cout << word << " -> " << count << '\n';
+    }
+
+    std::string line;
+    while (std::getline(std::cin, line)) {
+        std::cout << line.size() << std::endl;
+    }
+    return 0;
+}
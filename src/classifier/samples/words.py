@@ -0,0 +1,35 @@
+import sys
+from collections import Counter
+
+
+def read_words(path):
+    with open(path, "r", encoding="utf-8") as file:
+        for line in file:
+            yield from line.lower().split()
+
+
+def top_words(words, n=10):
+    counts = Counter(word.strip(".,;:!?") for word in words)
+    return counts.most_common(n)
+
+
+def main(args):
+    if len(args) < 2:
+        print("usage: words.py <file>")
+        return 1
+
+    try:
+        words = list(read_words(args[1]))
+    except FileNotFoundError as error:
+        print(f"error: {error}", file=sys.stderr)
+        return 1
+
+    squares = {word: len(word) ** 2 for word in words if word.isalpha()}
+    for word, count in top_words(words):
+        print(word, count, squares.get(word, 0))
+
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main(sys.argv))
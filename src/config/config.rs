@@ -0,0 +1,580 @@
+use crate::prelude::*;
+
+/// Tuning parameters of the pipeline, loaded once from a json file and overridden by arguments
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Config {
+    pub detection: DetectionConfig,
+    pub tracking: TrackingConfig,
+    pub sampling: SamplingConfig,
+    pub pipeline: PipelineConfig,
+    pub ocr: OcrConfig,
+    pub preprocessing: PreprocessingConfig,
+    pub classification: ClassificationConfig,
+    /// shows every analyzed frame with its detections, requires a display
+    pub preview: bool,
+    /// directory for frames, crops, ocr text and tokens of every analyzed frame
+    pub debug_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DetectionConfig {
+    pub detector: DetectorKind,
+    pub model_config: String,
+    pub weights: String,
+    pub conf_threshold: f32,
+    pub nms_threshold: f32,
+    pub input_width: i32,
+    pub input_height: i32,
+    /// pixels added left and right of a detection, so characters at its edges are not clipped
+    pub padding_x: i32,
+    /// pixels added above and below a detection
+    pub padding_y: i32,
+    pub heuristic: HeuristicConfig,
+}
+
+impl Default for DetectionConfig {
+    fn default() -> Self {
+        Self {
+            detector: DetectorKind::Auto,
+            model_config: "./weights/yolov4-obj.cfg".into(),
+            weights: "./weights/yolov4-obj_best.weights".into(),
+            conf_threshold: 0.5,
+            nms_threshold: 0.4,
+            input_width: 608,
+            input_height: 608,
+            padding_x: 4,
+            padding_y: 2,
+            heuristic: HeuristicConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DetectorKind {
+    Auto,
+    Heuristic,
+    Yolo,
+}
+
+impl std::str::FromStr for DetectorKind {
+    type Err = ExtractionError;
+
+    fn from_str(detector: &str) -> Result<Self, Self::Err> {
+        match detector {
+            "auto" => Ok(DetectorKind::Auto),
+            "heuristic" => Ok(DetectorKind::Heuristic),
+            "yolo" => Ok(DetectorKind::Yolo),
+            _ => Err(ExtractionError::Config(format!("unknown detector {}", detector))),
+        }
+    }
+}
+
+/// Parameters of the weights-free detector, kernel sizes are relative to the frame size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HeuristicConfig {
+    /// width of the kernel merging the characters of a line
+    pub line_kernel_ratio: f64,
+    /// height of the kernel merging the lines of a block
+    pub block_kernel_ratio: f64,
+    /// minimal area of a block relative to the frame
+    pub min_area_ratio: f64,
+    pub min_edge_density: f64,
+    pub max_edge_density: f64,
+}
+
+impl Default for HeuristicConfig {
+    fn default() -> Self {
+        Self {
+            line_kernel_ratio: 0.02,
+            block_kernel_ratio: 0.015,
+            min_area_ratio: 0.01,
+            min_edge_density: 0.03,
+            max_edge_density: 0.4,
+        }
+    }
+}
+
+/// Matching of the regions across frames, which gives a region a stable id while it stays on screen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TrackingConfig {
+    /// matches the regions across frames, otherwise every region of every frame gets a new id, e.g. for slides
+    pub enabled: bool,
+    /// minimal intersection over union of a region and the box of a track to continue the track
+    pub iou_threshold: f64,
+    /// frames in a row a region has to be seen in before it is reported, drops regions flashing up for single frames
+    pub min_hits: usize,
+    /// frames a reported region may be missing before its track ends
+    pub max_misses: usize,
+}
+
+impl Default for TrackingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            iou_threshold: 0.3,
+            min_hits: 2,
+            max_misses: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SamplingConfig {
+    pub mode: SamplingMode,
+    /// position the analysis starts at
+    pub start_seconds: f64,
+    /// position the analysis ends at, the end of the video if not set
+    pub end_seconds: Option<f64>,
+    /// delay between analyzed frames of the fixed sampling, fractions of a second are allowed
+    pub delay_seconds: f64,
+    /// delay between compared frames of the change sampling
+    pub check_interval_ms: f64,
+    /// gaps between two candidate frames longer than this are skipped by seeking instead of decoding
+    pub seek_threshold_ms: f64,
+    /// width the frames are downscaled to before comparing
+    pub compare_width: i32,
+    /// minimal intensity difference of a changed pixel
+    pub pixel_threshold: f64,
+    /// minimal fraction of changed pixels to analyze a frame
+    pub change_ratio: f64,
+    /// an unchanged screen is analyzed again after this delay, so the regions of a static slide are seen often enough to be reported
+    pub max_gap_ms: f64,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            mode: SamplingMode::Change,
+            start_seconds: 0.,
+            end_seconds: None,
+            delay_seconds: 1.,
+            check_interval_ms: 250.,
+            seek_threshold_ms: 2000.,
+            compare_width: 320,
+            pixel_threshold: 24.,
+            change_ratio: 0.0002,
+            max_gap_ms: 1000.,
+        }
+    }
+}
+
+/// Fixed sampling analyzes one frame every delay, change sampling only analyzes frames that differ from the last analyzed one
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SamplingMode {
+    Fixed,
+    Change,
+}
+
+impl std::str::FromStr for SamplingMode {
+    type Err = ExtractionError;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "fixed" => Ok(SamplingMode::Fixed),
+            "change" => Ok(SamplingMode::Change),
+            _ => Err(ExtractionError::Config(format!("unknown sampling mode {}", mode))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PipelineConfig {
+    /// threads running detection and OCR, each loads its own network
+    pub workers: usize,
+    /// maximal number of frames in flight and messages waiting for the parser
+    pub queue_size: usize,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            workers: thread::available_parallelism().map(|threads| threads.get().min(4)).unwrap_or(1),
+            queue_size: 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct OcrConfig {
+    pub engine: OcrKind,
+    /// tesseract language of the recognized text
+    pub language: String,
+    /// rebuilds line breaks and indentation from the word boxes and strips editor line numbers
+    pub reconstruct_layout: bool,
+}
+
+impl Default for OcrConfig {
+    fn default() -> Self {
+        Self {
+            engine: OcrKind::Tesseract,
+            language: "eng".into(),
+            reconstruct_layout: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OcrKind {
+    Tesseract,
+}
+
+impl std::str::FromStr for OcrKind {
+    type Err = ExtractionError;
+
+    fn from_str(engine: &str) -> Result<Self, Self::Err> {
+        match engine {
+            "tesseract" => Ok(OcrKind::Tesseract),
+            _ => Err(ExtractionError::Config(format!("unknown ocr engine {}", engine))),
+        }
+    }
+}
+
+/// Preparation of the regions for OCR, dark and light themes have their own ordered chain of steps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PreprocessingConfig {
+    /// mean brightness below which a region is a dark theme and gets inverted
+    pub inverse_threshold: f64,
+    /// steps applied to dark themes after the inversion
+    pub dark_theme: Vec<PreprocessingStep>,
+    pub light_theme: Vec<PreprocessingStep>,
+    /// regions narrower than this are upscaled to this width
+    pub upscale_width: i32,
+    pub max_upscale: f64,
+    /// filter strength of the denoising, higher values remove more noise but also details
+    pub denoise_strength: f32,
+    /// weight of the added edges of the sharpening
+    pub sharpen_amount: f64,
+    /// size of the neighborhood of the adaptive binarization in pixels
+    pub adaptive_block_size: i32,
+    /// constant subtracted from the neighborhood mean of the adaptive binarization
+    pub adaptive_offset: f64,
+    /// larger tilts are not corrected by the deskewing
+    pub max_skew_degrees: f64,
+}
+
+impl Default for PreprocessingConfig {
+    fn default() -> Self {
+        // denoising takes longer than the OCR itself, so only the cheap upscaling runs by default
+        let steps = vec![PreprocessingStep::Upscale];
+
+        Self {
+            inverse_threshold: 127.5,
+            dark_theme: steps.clone(),
+            light_theme: steps,
+            upscale_width: 1280,
+            max_upscale: 3.,
+            denoise_strength: 7.,
+            sharpen_amount: 0.8,
+            adaptive_block_size: 31,
+            adaptive_offset: 10.,
+            max_skew_degrees: 5.,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreprocessingStep {
+    /// enlarges small text
+    Upscale,
+    /// removes compression artifacts
+    Denoise,
+    /// restores blurred edges
+    Sharpen,
+    /// straightens tilted regions
+    Deskew,
+    /// global binarization
+    Otsu,
+    /// local binarization
+    Adaptive,
+}
+
+impl std::str::FromStr for PreprocessingStep {
+    type Err = ExtractionError;
+
+    fn from_str(step: &str) -> Result<Self, Self::Err> {
+        match step {
+            "upscale" => Ok(PreprocessingStep::Upscale),
+            "denoise" => Ok(PreprocessingStep::Denoise),
+            "sharpen" => Ok(PreprocessingStep::Sharpen),
+            "deskew" => Ok(PreprocessingStep::Deskew),
+            "otsu" => Ok(PreprocessingStep::Otsu),
+            "adaptive" => Ok(PreprocessingStep::Adaptive),
+            _ => Err(ExtractionError::Config(format!("unknown preprocessing step {}", step))),
+        }
+    }
+}
+
+/// Vote of the frames on the language of a video, the messages are buffered until the vote settles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ClassificationConfig {
+    /// characters apart from whitespace a text needs to vote
+    pub threshold: usize,
+    /// texts that vote at least before the language is chosen
+    pub min_votes: usize,
+    /// texts after which the leading language is chosen, even if its share is below the confidence,
+    /// also the number of texts a region buffers until its language is chosen
+    pub max_votes: usize,
+    /// share of the votes the leading language needs to be chosen
+    pub confidence: f64,
+    /// votes the languages named in the metadata add to the vote of every region
+    pub metadata_votes: f64,
+}
+
+impl Default for ClassificationConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 8,
+            min_votes: 5,
+            max_votes: 30,
+            confidence: 0.75,
+            metadata_votes: 3.,
+        }
+    }
+}
+
+impl Config {
+    pub fn load(file: &str) -> Result<Self, ExtractionError> {
+        let content = read_to_string(file)
+            .map_err(|err| ExtractionError::Config(format!("could not read config file {}: {}", file, err)))?;
+        let config = serde_json::from_str(&content)
+            .map_err(|err| ExtractionError::Config(format!("invalid config file {}: {}", file, err)))?;
+
+        Ok(config)
+    }
+
+    /// Loads the config file given as argument and applies the command line overrides
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, ExtractionError> {
+        let mut config = match matches.value_of("config") {
+            Some(file) => Self::load(file)?,
+            None => Self::default(),
+        };
+
+        if let Some(value) = matches.value_of("detector") {
+            config.detection.detector = parse_value("detector", value)?;
+        }
+        if let Some(value) = matches.value_of("ocr") {
+            config.ocr.engine = parse_value("ocr", value)?;
+        }
+        if let Some(value) = matches.value_of("model-config") {
+            config.detection.model_config = value.into();
+        }
+        if let Some(value) = matches.value_of("weights") {
+            config.detection.weights = value.into();
+        }
+        if let Some(value) = matches.value_of("conf-threshold") {
+            config.detection.conf_threshold = parse_value("conf-threshold", value)?;
+        }
+        if let Some(value) = matches.value_of("nms-threshold") {
+            config.detection.nms_threshold = parse_value("nms-threshold", value)?;
+        }
+        if let Some(value) = matches.value_of("input-size") {
+            config.detection.input_width = parse_value("input-size", value)?;
+            config.detection.input_height = config.detection.input_width;
+        }
+        if let Some(value) = matches.value_of("workers") {
+            config.pipeline.workers = parse_value("workers", value)?;
+        }
+        if let Some(value) = matches.value_of("sampling") {
+            config.sampling.mode = parse_value("sampling", value)?;
+        }
+        if let Some(value) = matches.value_of("start") {
+            config.sampling.start_seconds = parse_time("start", value)?;
+        }
+        if let Some(value) = matches.value_of("end") {
+            config.sampling.end_seconds = Some(parse_time("end", value)?);
+        }
+        if let Some(value) = matches.value_of("delay-seconds") {
+            config.sampling.delay_seconds = parse_value("delay-seconds", value)?;
+        }
+        if let Some(value) = matches.value_of("inverse-threshold") {
+            config.preprocessing.inverse_threshold = parse_value("inverse-threshold", value)?;
+        }
+        if let Some(value) = matches.value_of("preprocessing") {
+            let steps = parse_steps(value)?;
+            config.preprocessing.dark_theme = steps.clone();
+            config.preprocessing.light_theme = steps;
+        }
+        if let Some(value) = matches.value_of("classification-threshold") {
+            config.classification.threshold = parse_value("classification-threshold", value)?;
+        }
+        if matches.is_present("preview") {
+            config.preview = true;
+        }
+        if let Some(value) = matches.value_of("debug-dir") {
+            config.debug_dir = Some(value.into());
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Rejects values the pipeline cannot work with, whether they come from the config file or the command line
+    pub fn validate(&self) -> Result<(), ExtractionError> {
+        let sampling = &self.sampling;
+        if !sampling.start_seconds.is_finite() || sampling.start_seconds < 0. {
+            return Err(invalid("start", sampling.start_seconds));
+        }
+        if let Some(end_seconds) = sampling.end_seconds {
+            if end_seconds.is_nan() || end_seconds <= sampling.start_seconds {
+                return Err(ExtractionError::Config(format!("end {} is not after start {}", end_seconds, sampling.start_seconds)));
+            }
+        }
+        if !sampling.delay_seconds.is_finite() || sampling.delay_seconds <= 0. {
+            return Err(invalid("delay-seconds", sampling.delay_seconds));
+        }
+        if !sampling.check_interval_ms.is_finite() || sampling.check_interval_ms <= 0. {
+            return Err(invalid("checkIntervalMs", sampling.check_interval_ms));
+        }
+        if sampling.max_gap_ms.is_nan() || sampling.max_gap_ms < 0. {
+            return Err(invalid("maxGapMs", sampling.max_gap_ms));
+        }
+        if !(0. ..=1.).contains(&sampling.change_ratio) {
+            return Err(invalid("changeRatio", sampling.change_ratio));
+        }
+
+        let detection = &self.detection;
+        if !(0. ..=1.).contains(&detection.conf_threshold) {
+            return Err(invalid("conf-threshold", detection.conf_threshold));
+        }
+        if !(0. ..=1.).contains(&detection.nms_threshold) {
+            return Err(invalid("nms-threshold", detection.nms_threshold));
+        }
+        if detection.input_width <= 0 || detection.input_height <= 0 {
+            return Err(invalid("input-size", detection.input_width));
+        }
+        if self.pipeline.workers == 0 {
+            return Err(invalid("workers", self.pipeline.workers));
+        }
+        if !(0. ..=255.).contains(&self.preprocessing.inverse_threshold) {
+            return Err(invalid("inverse-threshold", self.preprocessing.inverse_threshold));
+        }
+        if !(0. ..=1.).contains(&self.classification.confidence) {
+            return Err(invalid("confidence", self.classification.confidence));
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid<T: std::fmt::Display>(name: &str, value: T) -> ExtractionError {
+    ExtractionError::Config(format!("invalid value '{}' for {}", value, name))
+}
+
+/// Parses a position given in seconds or as `mm:ss` or `hh:mm:ss`, seconds may have a fraction
+fn parse_time(name: &str, value: &str) -> Result<f64, ExtractionError> {
+    let invalid = || ExtractionError::Config(format!("invalid time '{}' for --{}", value, name));
+    let parts: Vec<&str> = value.split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+
+    parts.iter().enumerate().try_fold(0., |seconds, (idx, part)| {
+        let part: f64 = part.parse().map_err(|_| invalid())?;
+        // minutes and seconds after the first part stay below a minute, e.g. `1:30` but not `1:90`
+        if !part.is_finite() || part < 0. || (idx > 0 && part >= 60.) {
+            return Err(invalid());
+        }
+        Ok(seconds * 60. + part)
+    })
+}
+
+/// Parses a comma separated list of preprocessing steps, `none` disables the preprocessing
+fn parse_steps(value: &str) -> Result<Vec<PreprocessingStep>, ExtractionError> {
+    if value == "none" {
+        return Ok(Vec::new());
+    }
+
+    value.split(',')
+        .map(|step| parse_value("preprocessing", step.trim()))
+        .collect()
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, ExtractionError> {
+    value.parse::<T>()
+        .map_err(|_| ExtractionError::Config(format!("invalid value '{}' for --{}", value, name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_args(args: &[&str]) -> Result<Config, ExtractionError> {
+        let matches = App::new("test")
+            .arg(Arg::with_name("config").long("config").takes_value(true))
+            .arg(Arg::with_name("start").long("start").takes_value(true))
+            .arg(Arg::with_name("end").long("end").takes_value(true))
+            .arg(Arg::with_name("delay-seconds").long("delay-seconds").takes_value(true))
+            .arg(Arg::with_name("conf-threshold").long("conf-threshold").takes_value(true))
+            .arg(Arg::with_name("preprocessing").long("preprocessing").takes_value(true))
+            .get_matches_from(std::iter::once("test").chain(args.iter().copied()));
+
+        Config::from_matches(&matches)
+    }
+
+    #[test]
+    fn parses_seconds_and_clock_times() {
+        assert_eq!(parse_time("start", "90").unwrap(), 90.);
+        assert_eq!(parse_time("start", "1.5").unwrap(), 1.5);
+        assert_eq!(parse_time("start", "1:30").unwrap(), 90.);
+        assert_eq!(parse_time("start", "1:02:03.5").unwrap(), 3723.5);
+    }
+
+    #[test]
+    fn rejects_invalid_times() {
+        for value in ["1:-30", "-5", "1:90", "1:2:3:4", "nan", "inf", "1:", "a:30"] {
+            assert!(matches!(parse_time("start", value), Err(ExtractionError::Config(_))), "{}", value);
+        }
+    }
+
+    #[test]
+    fn parses_preprocessing_steps() {
+        assert_eq!(parse_steps("upscale, otsu").unwrap(), vec![PreprocessingStep::Upscale, PreprocessingStep::Otsu]);
+        assert!(parse_steps("none").unwrap().is_empty());
+        assert!(matches!(parse_steps("upscale,blur"), Err(ExtractionError::Config(_))));
+    }
+
+    #[test]
+    fn arguments_override_the_config_file() {
+        let dir = test_dir("config", "override");
+        write_files(&dir, &[("config.json", r#"{"sampling": {"delaySeconds": 2.0, "startSeconds": 10.0}, "detection": {"confThreshold": 0.7}}"#)]);
+        let file = dir.join("config.json");
+
+        let config = from_args(&["--config", file.to_str().unwrap(), "--delay-seconds", "0.5"]).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        // the argument wins, values without an argument come from the file and the rest are defaults
+        assert_eq!(config.sampling.delay_seconds, 0.5);
+        assert_eq!(config.sampling.start_seconds, 10.);
+        assert_eq!(config.detection.conf_threshold, 0.7);
+        assert_eq!(config.sampling.check_interval_ms, SamplingConfig::default().check_interval_ms);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for args in [
+            &["--start", "1:00", "--end", "0:30"][..],
+            &["--start", "30", "--end", "30"][..],
+            &["--delay-seconds", "0"][..],
+            &["--delay-seconds=-1"][..],
+            &["--delay-seconds", "NaN"][..],
+            &["--conf-threshold", "NaN"][..],
+            &["--conf-threshold", "1.5"][..],
+        ] {
+            assert!(matches!(from_args(args), Err(ExtractionError::Config(_))), "{:?}", args);
+        }
+        assert!(from_args(&["--start", "0:30", "--end", "1:00", "--preprocessing", "none"]).is_ok());
+    }
+}
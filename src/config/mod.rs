@@ -0,0 +1 @@
+pub(crate) mod config;
\ No newline at end of file
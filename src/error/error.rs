@@ -0,0 +1,67 @@
+use crate::prelude::*;
+
+/// Errors of the extraction pipeline, grouped by the stage they occur in
+#[derive(Debug)]
+pub enum ExtractionError {
+    Download(String),
+    Detection(String),
+    Ocr(String),
+    Classification(String),
+    Parse(String),
+    Config(String),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for ExtractionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ExtractionError::Download(msg) => write!(f, "download failed: {}", msg),
+            ExtractionError::Detection(msg) => write!(f, "detection failed: {}", msg),
+            ExtractionError::Ocr(msg) => write!(f, "optical character recognition failed: {}", msg),
+            ExtractionError::Classification(msg) => write!(f, "classification failed: {}", msg),
+            ExtractionError::Parse(msg) => write!(f, "parsing failed: {}", msg),
+            ExtractionError::Config(msg) => write!(f, "invalid configuration: {}", msg),
+            ExtractionError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ExtractionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExtractionError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ExtractionError {
+    fn from(err: std::io::Error) -> Self {
+        ExtractionError::Io(err)
+    }
+}
+
+impl<T> From<mpsc::SendError<T>> for ExtractionError {
+    fn from(_err: mpsc::SendError<T>) -> Self {
+        ExtractionError::Detection("parser thread stopped receiving".into())
+    }
+}
+
+#[cfg(feature = "video")]
+impl From<opencv::Error> for ExtractionError {
+    fn from(err: opencv::Error) -> Self {
+        ExtractionError::Detection(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_names_the_stage_without_prefix() {
+        let err = ExtractionError::Download("no network".into());
+        assert_eq!(err.to_string(), "download failed: no network");
+        assert_eq!(format!("Error analyzing x: {}", err), "Error analyzing x: download failed: no network");
+    }
+}
@@ -0,0 +1 @@
+pub(crate) mod error;
\ No newline at end of file
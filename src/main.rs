@@ -1,26 +1,45 @@
 // #![allow(unused)]
 mod prelude {
     pub use crate::{
-        analyzer::analyzer::*,
-        classifier::classifier::*,
-        neural_net::yolo::Yolo, 
-        parser::{Parser, ProtoParser, knowledge_component::*, cjparser::CJParser, pyparser::PyParser},
+        analyzer::{analyzer::*, batch::*, image::*, playlist::*, text::*},
+        classifier::{classifier::*, naive_bayes::NaiveBayes},
+        config::config::*,
+        error::error::*,
+        reconstruction::reconstruction::*,
+        parser::{Parser, ProtoParser, knowledge_component::*, cjparser::CJParser, pyparser::PyParser, router::*},
         lexer::pylexer::*,
         lexer::cjlexer::*,
+        source::{Playlist, PlaylistSource, VideoMetadata, VideoSource, fixture::Fixture, local::LocalFile, ytdlp::{DEFAULT_FORMAT, YtDlp}},
     };
+    #[cfg(feature = "video")]
+    pub use crate::neural_net::{debug::*, detector::*, heuristic::HeuristicDetector, layout::reconstruct_layout, ocr::*, pipeline::*, preprocessing::preprocess, sampler::FrameSampler, tracker::RegionTracker, yolo::Yolo};
 
-    pub use clap::{App, Arg};
-    pub use indexmap::IndexSet;
+    pub use clap::{App, Arg, ArgMatches};
+    pub use indexmap::{IndexMap, IndexSet};
     pub use logos::{Logos, Lexer};
-    pub use opencv::{core::{BORDER_DEFAULT, CV_32F, Point, Range, Rect, Rect2i, Rect_, Scalar, Size, create_continuous, min_max_loc, no_array, subtract}, dnn::{self, DNN_BACKEND_OPENCV, DNN_TARGET_CPU, Net, nms_boxes, read_net_from_darknet}, highgui, imgproc::{COLOR_BGR2GRAY, COLOR_BGR5552GRAY, COLOR_BGRA2GRAY, COLOR_RGB2GRAY, LINE_8, THRESH_BINARY, cvt_color, gaussian_blur, rectangle, threshold}, prelude::{Mat, MatTrait, MatTraitManual, NetTrait}, text::{OCRTesseract, OEM_DEFAULT, PSM_SINGLE_BLOCK}, types::{VectorOfMat, VectorOfRect, VectorOfString, VectorOff32, VectorOfi32}, videoio::{self, CAP_PROP_FPS, CAP_PROP_POS_MSEC, VideoCapture, VideoCaptureTrait}};
+    #[cfg(feature = "video")]
+    pub use opencv::{core::{BORDER_CONSTANT, BORDER_DEFAULT, BORDER_REPLICATE, CV_32F, Point, Point2f, Ptr, Range, Rect, Rect2i, Rect_, Scalar, Size, absdiff, add_weighted, count_non_zero, create_continuous, find_non_zero, min_max_loc, no_array, subtract}, dnn::{self, DNN_BACKEND_OPENCV, DNN_TARGET_CPU, Net, nms_boxes, read_net_from_darknet}, highgui, imgcodecs::{IMREAD_COLOR, imread, imwrite}, imgproc::{ADAPTIVE_THRESH_GAUSSIAN_C, CHAIN_APPROX_SIMPLE, COLOR_BGR2GRAY, COLOR_BGR5552GRAY, COLOR_BGRA2GRAY, COLOR_RGB2GRAY, INTER_AREA, INTER_CUBIC, LINE_8, MORPH_CLOSE, MORPH_GRADIENT, MORPH_RECT, RETR_EXTERNAL, THRESH_BINARY, THRESH_BINARY_INV, THRESH_OTSU, adaptive_threshold, bounding_rect, canny, cvt_color, find_contours, gaussian_blur, get_rotation_matrix_2d, get_structuring_element, min_area_rect, morphology_default_border_value, morphology_ex, rectangle, resize, threshold, warp_affine}, photo::fast_nl_means_denoising, prelude::{Mat, MatTrait, MatTraitManual, NetTrait}, text::{OCRTesseract, OCR_LEVEL_WORD, OEM_DEFAULT, PSM_SINGLE_BLOCK}, types::{VectorOfMat, VectorOfRect, VectorOfString, VectorOfVectorOfPoint, VectorOff32, VectorOfi32}, videoio::{self, CAP_PROP_POS_MSEC, VideoCapture, VideoCaptureTrait}};
     pub use serde::{Serialize, Deserialize};
-    pub use std::{env::current_dir, error::Error, fs::write, fs::create_dir, hash::Hash, hash::Hasher, process::Command, str::from_utf8, sync::{Arc, Mutex, mpsc::{self, Sender, Receiver}}, thread, time::Duration};
+    pub use std::{collections::{BTreeSet, HashMap, HashSet}, io::{Read, Write}, error::Error, fs::write, fs::create_dir_all, fs::read_dir, fs::read_to_string, hash::Hash, hash::Hasher, path::{Path, PathBuf}, process::Command, sync::{Arc, OnceLock, mpsc}, thread};
+    #[cfg(any(feature = "video", test))]
+    pub use std::collections::BTreeMap;
+    #[cfg(test)]
+    pub use crate::testing::files::{test_dir, write_files};
+    #[cfg(feature = "video")]
+    pub use std::{env::current_dir, fs::OpenOptions, sync::{Mutex, mpsc::{Receiver, SyncSender}}};
 }
 mod analyzer;
 mod classifier;
+mod config;
+mod error;
 mod lexer;
+#[cfg(feature = "video")]
 mod neural_net;
 mod parser;
+mod reconstruction;
+mod source;
+#[cfg(test)]
+mod testing;
 
 use crate::prelude::*;
 
@@ -28,13 +47,12 @@ use crate::prelude::*;
 
 fn main() {
     if let Err(err) = try_main() {
-        eprintln!("{}", err);
+        eprintln!("Error: {}", err);
         std::process::exit(1);
     }
 }
 
 fn try_main() -> Result<(), Box<dyn Error>> {
-    // TODO: implement playlist parameter
     // argument parsing
     let matches = App::new("Knowledge Component Extraction")
         .version("0.1.0")
@@ -44,32 +62,182 @@ fn try_main() -> Result<(), Box<dyn Error>> {
                 .long("url")
                 .takes_value(true)
                 .help("URL as input"))
+        .arg(Arg::with_name("config")
+                .short("c")
+                .long("config")
+                .takes_value(true)
+                .help("Json config file with pipeline thresholds and model paths"))
+        .arg(Arg::with_name("detector")
+                .long("detector")
+                .takes_value(true)
+                .possible_values(&["auto", "heuristic", "yolo"])
+                .help("Detects code with the YOLO weights or the weights-free heuristic, auto prefers the weights if present"))
+        .arg(Arg::with_name("ocr")
+                .long("ocr")
+                .takes_value(true)
+                .possible_values(&["tesseract"])
+                .help("Reads the detected regions with Tesseract"))
+        .arg(Arg::with_name("model-config")
+                .long("model-config")
+                .takes_value(true)
+                .help("Overrides the darknet model configuration"))
+        .arg(Arg::with_name("weights")
+                .long("weights")
+                .takes_value(true)
+                .help("Overrides the darknet model weights"))
+        .arg(Arg::with_name("conf-threshold")
+                .long("conf-threshold")
+                .takes_value(true)
+                .help("Overrides the detection confidence threshold"))
+        .arg(Arg::with_name("nms-threshold")
+                .long("nms-threshold")
+                .takes_value(true)
+                .help("Overrides the non maximum suppression threshold"))
+        .arg(Arg::with_name("input-size")
+                .long("input-size")
+                .takes_value(true)
+                .help("Overrides the input width and height of the neural net"))
+        .arg(Arg::with_name("workers")
+                .long("workers")
+                .takes_value(true)
+                .help("Number of threads running detection and OCR"))
+        .arg(Arg::with_name("sampling")
+                .long("sampling")
+                .takes_value(true)
+                .possible_values(&["change", "fixed"])
+                .help("Analyzes frames when the screen changed or at a fixed delay"))
+        .arg(Arg::with_name("start")
+                .long("start")
+                .takes_value(true)
+                .help("Starts the analysis at the position given in seconds or as [hh:]mm:ss"))
+        .arg(Arg::with_name("end")
+                .long("end")
+                .takes_value(true)
+                .help("Ends the analysis at the position given in seconds or as [hh:]mm:ss"))
+        .arg(Arg::with_name("delay-seconds")
+                .long("delay-seconds")
+                .takes_value(true)
+                .help("Overrides the delay between analyzed frames of the fixed sampling, fractions of a second are allowed"))
+        .arg(Arg::with_name("inverse-threshold")
+                .long("inverse-threshold")
+                .takes_value(true)
+                .help("Overrides the mean brightness below which code regions are inverted"))
+        .arg(Arg::with_name("preprocessing")
+                .long("preprocessing")
+                .takes_value(true)
+                .help("Comma separated preprocessing steps for both themes out of upscale, denoise, sharpen, deskew, otsu and adaptive, or none"))
+        .arg(Arg::with_name("classification-threshold")
+                .long("classification-threshold")
+                .takes_value(true)
+                .help("Overrides the number of characters a text needs to vote on the language"))
+        .arg(Arg::with_name("preview")
+                .long("preview")
+                .help("Shows the analyzed frames with their detections, press 'q' or 'esc' to stop"))
+        .arg(Arg::with_name("debug-dir")
+                .long("debug-dir")
+                .takes_value(true)
+                .help("Writes frames, crops, ocr text and tokens of every analyzed frame to the directory"))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .default_value(DEFAULT_FORMAT)
+                .help("yt-dlp format selection for downloading videos"))
+        .arg(Arg::with_name("playlist")
+                .long("playlist")
+                .takes_value(true)
+                .help("Playlist URL or local json playlist listing as input"))
         .arg(Arg::with_name("path")
                 .short("p")
                 .long("path")
                 .takes_value(true)
                 .help("Path as input"))
+        .arg(Arg::with_name("recursive")
+                .short("r")
+                .long("recursive")
+                .requires("path")
+                .help("Searches the input path recursively for videos"))
         .arg(Arg::with_name("file")
                 .short("f")
                 .long("file")
                 .takes_value(true)
                 .help("File as input"))
+        .arg(Arg::with_name("image")
+                .short("i")
+                .long("image")
+                .takes_value(true)
+                .help("Image or directory of images as input"))
+        .arg(Arg::with_name("source")
+                .short("s")
+                .long("source")
+                .takes_value(true)
+                .multiple(true)
+                .help("Source code files as input, '-' reads from stdin"))
+        .arg(Arg::with_name("language")
+                .short("l")
+                .long("language")
+                .takes_value(true)
+                .possible_values(&["c", "cpp", "java", "python"])
+                .help("Programming language of the source code, classified if omitted"))
         .get_matches();
 
+    let config = Arc::new(Config::from_matches(&matches)?);
+
 
     if let Some(url) = matches.value_of("url") {
-        let mut analyzer = VideoAnalyzer::new(url);
+        let source = Arc::new(YtDlp::with_format(matches.value_of("format").unwrap_or(DEFAULT_FORMAT)));
+        let mut analyzer = VideoAnalyzer::from_source(source, url, config.clone())?;
+        analyzer.run()?;
+        analyzer.save_result()?;
+    }
+
+    if let Some(playlist) = matches.value_of("playlist") {
+        let mut analyzer = if Path::new(playlist).is_file() {
+            PlaylistAnalyzer::new(Arc::new(Fixture::new(playlist)), playlist, config.clone())?
+        } else {
+            let format = matches.value_of("format").unwrap_or(DEFAULT_FORMAT);
+            PlaylistAnalyzer::new(Arc::new(YtDlp::with_format(format)), playlist, config.clone())?
+        };
         analyzer.run()?;
         analyzer.save_result()?;
-        // analyzer.download_video()?;
     }
 
     if let Some(path) = matches.value_of("path") {
-        // TODO: implement
+        let mut batch = BatchAnalyzer::new(path, matches.is_present("recursive"), config.clone());
+        batch.run()?;
+        batch.save_result()?;
     } 
 
     if let Some(file) = matches.value_of("file") {
-        // TODO: implement
+        let mut analyzer = VideoAnalyzer::from_file(file, config.clone())?;
+        analyzer.run()?;
+        analyzer.save_result()?;
+    }
+
+    if let Some(image) = matches.value_of("image") {
+        let mut analyzer = ImageAnalyzer::new(image, config.clone())?;
+        analyzer.run()?;
+        analyzer.save_result()?;
+    }
+
+    if let Some(sources) = matches.values_of("source") {
+        let language = match matches.value_of("language") {
+            Some(language) => Some(language.parse::<ProgrammingLanguage>()?),
+            None => None,
+        };
+
+        let mut analyzers = Vec::new();
+        for source in sources {
+            analyzers.push(match source {
+                "-" => TextAnalyzer::from_stdin()?,
+                _ => TextAnalyzer::from_file(source)?,
+            });
+        }
+        TextAnalyzer::name_apart(&mut analyzers);
+
+        for mut analyzer in analyzers {
+            analyzer.run(language)?;
+            analyzer.save_result()?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,96 @@
+use crate::prelude::*;
+
+/// Writes the intermediate artifacts of every analyzed frame for inspecting the pipeline
+#[derive(Debug)]
+pub struct DebugDump {
+    pub directory: PathBuf,
+    pub sample: usize,
+}
+
+/// Manifest entry tying the artifacts of a frame together, the tokens are added by the parser once the languages of the regions are known
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugSample {
+    pub sample: usize,
+    pub time_code: i32,
+    pub frame: String,
+    pub crops: Vec<String>,
+    pub ocr: String,
+    /// tracked id of every region
+    pub regions: Vec<usize>,
+    /// recognized words of every region
+    pub words: Vec<Vec<Word>>,
+    /// tokens of every region, lexed by the parser of the region's language
+    pub tokens: Vec<Vec<String>>,
+    /// recognized text of every region
+    #[serde(skip)]
+    pub texts: Vec<String>,
+    #[serde(skip)]
+    pub manifest: PathBuf,
+}
+
+impl DebugDump {
+    /// Creates the debug directory of a video if a debug directory is configured
+    pub fn from_config(config: &Config, file: &str) -> Result<Option<Self>, ExtractionError> {
+        match &config.debug_dir {
+            Some(debug_dir) => Ok(Some(Self::new(debug_dir, &get_file_title(file))?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn new(debug_dir: &str, name: &str) -> Result<Self, ExtractionError> {
+        let directory = Path::new(debug_dir).join(name);
+        create_dir_all(&directory)?;
+        // start a fresh manifest for every run
+        write(directory.join("manifest.jsonl"), "")?;
+
+        Ok(Self {
+            directory: directory,
+            sample: 0,
+        })
+    }
+
+    pub fn write_sample(&mut self, time_code: i32, frame: &Mat, crops: &[Mat], texts: &[(Region, OcrOutput)]) -> Result<DebugSample, ExtractionError> {
+        self.sample += 1;
+        let prefix = format!("sample_{:05}", self.sample);
+
+        let frame_file = format!("{}_frame.png", prefix);
+        imwrite(&self.directory.join(&frame_file).display().to_string(), frame, &VectorOfi32::new())?;
+
+        let mut crop_files = Vec::with_capacity(crops.len());
+        for (idx, crop) in crops.iter().enumerate() {
+            if crop.empty()? {
+                continue;
+            }
+            let crop_file = format!("{}_crop_{}.png", prefix, idx);
+            imwrite(&self.directory.join(&crop_file).display().to_string(), crop, &VectorOfi32::new())?;
+            crop_files.push(crop_file);
+        }
+
+        let ocr: Vec<_> = texts.iter().map(|(_, ocr_output)| ocr_output.text.as_str()).collect();
+        let ocr = ocr.join("\n");
+        write(self.directory.join(format!("{}_ocr.txt", prefix)), &ocr)?;
+
+        Ok(DebugSample {
+            sample: self.sample,
+            time_code: time_code,
+            frame: frame_file,
+            crops: crop_files,
+            ocr: ocr,
+            regions: texts.iter().map(|(region, _)| region.id).collect(),
+            words: texts.iter().map(|(_, ocr_output)| ocr_output.words.clone()).collect(),
+            tokens: Vec::new(),
+            texts: texts.iter().map(|(_, ocr_output)| ocr_output.text.clone()).collect(),
+            manifest: self.directory.join("manifest.jsonl"),
+        })
+    }
+}
+
+impl DebugSample {
+    pub fn append_to_manifest(&self) -> Result<(), ExtractionError> {
+        let mut manifest = OpenOptions::new().append(true).create(true).open(&self.manifest)?;
+        writeln!(manifest, "{}", serde_json::to_string(self).map_err(|err| ExtractionError::Io(err.into()))?)?;
+
+        Ok(())
+    }
+}
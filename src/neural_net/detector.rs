@@ -0,0 +1,82 @@
+use crate::prelude::*;
+
+/// Finds the source code regions of a frame
+pub trait CodeDetector {
+    /// Returns the bounding boxes of the detected regions along with their confidences
+    fn detect(&mut self, img: &Mat) -> Result<Vec<(Rect, f32)>, ExtractionError>;
+}
+
+/// Creates the configured detector, `auto` uses the YOLO network if its files exist and the heuristic detector otherwise
+pub fn create_detector(config: &DetectionConfig) -> Result<Box<dyn CodeDetector>, ExtractionError> {
+    let weights_available = Path::new(&config.model_config).is_file() && Path::new(&config.weights).is_file();
+
+    match config.detector {
+        DetectorKind::Yolo => Ok(Box::new(Yolo::new(config)?)),
+        DetectorKind::Auto if weights_available => Ok(Box::new(Yolo::new(config)?)),
+        DetectorKind::Auto | DetectorKind::Heuristic => Ok(Box::new(HeuristicDetector::new(config))),
+    }
+}
+
+/// Returns every region of the frame along with its preprocessed sub image
+pub fn extract_regions(img: &Mat, detections: Vec<(Rect, f32)>, config: &Config) -> Result<Vec<(Region, Mat)>, ExtractionError> {
+    let mut gray = Mat::default();
+    let mut regions = Vec::with_capacity(detections.len());
+
+    for (id, (mut bbox, confidence)) in detections.into_iter().enumerate() {
+        // pad the bounding box, detections tend to cut through the characters at their edges
+        bbox.x -= config.detection.padding_x;
+        bbox.y -= config.detection.padding_y;
+        bbox.width += 2 * config.detection.padding_x;
+        bbox.height += 2 * config.detection.padding_y;
+
+        // adjust bounding box if it exceeds the frame
+        if bbox.x < 0 {
+            bbox.width += bbox.x;
+            bbox.x = 0;
+        }
+        if bbox.y < 0 {
+            bbox.height += bbox.y;
+            bbox.y = 0;
+        }
+        bbox.width = bbox.width.min(img.cols() - bbox.x);
+        bbox.height = bbox.height.min(img.rows() - bbox.y);
+
+        if bbox.width <= 0 || bbox.height <= 0 {
+            continue;
+        }
+
+        // get sub image and convert to grayscale
+        let sub_img = Mat::roi(img, bbox)?;
+        cvt_color(&sub_img, &mut gray, COLOR_BGR2GRAY, 0)?;
+        let src = preprocess(&gray, &config.preprocessing)?;
+
+        let region = Region {
+            id: id,
+            bbox: BoundingBox { x: bbox.x, y: bbox.y, width: bbox.width, height: bbox.height },
+            confidence: confidence,
+        };
+        regions.push((region, src));
+    }
+
+    Ok(regions)
+}
+
+/// Returns a copy of the frame with the regions drawn in, the frame itself stays untouched for the OCR
+pub fn draw_regions(img: &Mat, regions: &[(Region, Mat)]) -> Result<Mat, ExtractionError> {
+    let mut preview = img.try_clone()?;
+
+    for (region, _) in regions {
+        let bbox = Rect::new(region.bbox.x, region.bbox.y, region.bbox.width, region.bbox.height);
+        // draw predicted bounding box
+        rectangle(
+            &mut preview,
+            bbox,
+            Scalar::new(255., 18., 50., 0.0),
+            2,
+            LINE_8,
+            0
+        )?;
+    }
+
+    Ok(preview)
+}
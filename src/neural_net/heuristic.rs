@@ -0,0 +1,127 @@
+use crate::prelude::*;
+
+/// Detects blocks of text with classical image processing, works without trained weights.
+/// Characters are merged into lines and lines into blocks by morphological closing,
+/// the contours of the blocks are kept if their size and edge density are typical for source code.
+pub struct HeuristicDetector {
+    config: HeuristicConfig,
+    conf_threshold: f32,
+}
+
+impl HeuristicDetector {
+    pub fn new(config: &DetectionConfig) -> Self {
+        Self {
+            config: config.heuristic.clone(),
+            conf_threshold: config.conf_threshold,
+        }
+    }
+}
+
+impl CodeDetector for HeuristicDetector {
+    fn detect(&mut self, img: &Mat) -> Result<Vec<(Rect, f32)>, ExtractionError> {
+        // preallocate image matrices
+        let mut gray = Mat::default();
+        let mut gradient = Mat::default();
+        let mut binary = Mat::default();
+        let mut lines = Mat::default();
+        let mut blocks = Mat::default();
+        let mut edges = Mat::default();
+
+        let anchor = Point::new(-1, -1);
+        let border_value = morphology_default_border_value()?;
+        let img_width = img.cols();
+        let img_height = img.rows();
+
+        cvt_color(img, &mut gray, COLOR_BGR2GRAY, 0)?;
+
+        // the morphological gradient highlights the strokes of the characters on light and dark themes
+        let kernel = get_structuring_element(MORPH_RECT, Size::new(3, 3), anchor)?;
+        morphology_ex(&gray, &mut gradient, MORPH_GRADIENT, &kernel, anchor, 1, BORDER_CONSTANT, border_value)?;
+        threshold(&gradient, &mut binary, 0., 255., THRESH_BINARY | THRESH_OTSU)?;
+
+        // merge the characters of a line, then the lines of a block
+        let line_width = ((img_width as f64 * self.config.line_kernel_ratio) as i32).max(1);
+        let line_kernel = get_structuring_element(MORPH_RECT, Size::new(line_width, 1), anchor)?;
+        morphology_ex(&binary, &mut lines, MORPH_CLOSE, &line_kernel, anchor, 1, BORDER_CONSTANT, border_value)?;
+
+        let block_height = ((img_height as f64 * self.config.block_kernel_ratio) as i32).max(1);
+        let block_kernel = get_structuring_element(MORPH_RECT, Size::new(1, block_height), anchor)?;
+        morphology_ex(&lines, &mut blocks, MORPH_CLOSE, &block_kernel, anchor, 1, BORDER_CONSTANT, border_value)?;
+
+        let mut contours = VectorOfVectorOfPoint::new();
+        find_contours(&blocks, &mut contours, RETR_EXTERNAL, CHAIN_APPROX_SIMPLE, Point::new(0, 0))?;
+        canny(&gray, &mut edges, 50., 150., 3, false)?;
+
+        let img_area = (img_width * img_height).max(1) as f64;
+        let mut detections = Vec::new();
+
+        for contour in contours.iter() {
+            let bbox = bounding_rect(&contour)?;
+            let area = (bbox.width * bbox.height).max(1) as f64;
+
+            // drop blobs that are too small to hold code, such as icons or single words
+            if area / img_area < self.config.min_area_ratio {
+                continue;
+            }
+
+            // text is busier than flat backgrounds but calmer than photos or noise
+            let edge_density = count_non_zero(&Mat::roi(&edges, bbox)?)? as f64 / area;
+            if edge_density < self.config.min_edge_density || edge_density > self.config.max_edge_density {
+                continue;
+            }
+
+            // merged code blocks are close to rectangular, the fill ratio serves as confidence
+            let confidence = (count_non_zero(&Mat::roi(&blocks, bbox)?)? as f64 / area) as f32;
+            if confidence > self.conf_threshold {
+                detections.push((bbox, confidence));
+            }
+        }
+
+        Ok(detections)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// White frame with a block of dark code lines of similar length, the block spans x 40 to about 320 and y 40 to 170
+    fn code_frame() -> Mat {
+        let mut img = Mat::new_rows_cols_with_default(360, 640, opencv::core::CV_8UC3, Scalar::all(255.)).unwrap();
+        let lines = [
+            "int sum(const int *v, int n) {",
+            "    int total = 0; int i = 0;",
+            "    for (i = 0; i < n; i++) {",
+            "        total = total + v[i];",
+            "    } printf(\"%d\\n\", total);",
+            "    return total; /* sum */ }",
+            "int main(void) { int v[3]; }",
+            "/* values are summed above */",
+        ];
+        for (idx, line) in lines.iter().enumerate() {
+            let origin = Point::new(40, 52 + 16 * idx as i32);
+            opencv::imgproc::put_text(&mut img, line, origin, opencv::imgproc::FONT_HERSHEY_SIMPLEX, 0.45, Scalar::all(0.), 1, LINE_8, false).unwrap();
+        }
+        img
+    }
+
+    #[test]
+    fn detects_block_of_code() {
+        let mut detector = HeuristicDetector::new(&DetectionConfig::default());
+        let detections = detector.detect(&code_frame()).unwrap();
+
+        assert!(!detections.is_empty());
+        for (bbox, confidence) in detections {
+            assert!(bbox.x >= 30 && bbox.y >= 30 && bbox.x + bbox.width <= 360 && bbox.y + bbox.height <= 210, "{:?}", bbox);
+            assert!(confidence > 0.5 && confidence <= 1.);
+        }
+    }
+
+    #[test]
+    fn blank_frame_has_no_code() {
+        let mut detector = HeuristicDetector::new(&DetectionConfig::default());
+        let blank = Mat::new_rows_cols_with_default(360, 640, opencv::core::CV_8UC3, Scalar::all(255.)).unwrap();
+
+        assert!(detector.detect(&blank).unwrap().is_empty());
+    }
+}
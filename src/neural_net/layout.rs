@@ -0,0 +1,194 @@
+use crate::prelude::*;
+
+/// Rebuilds line breaks and indentation of a region from its word boxes.
+/// Words are grouped into lines by their vertical centers and placed on a monospace grid,
+/// whose character width is estimated from the words, editor line numbers are stripped.
+pub fn reconstruct_layout(words: &[Word]) -> String {
+    let mut lines = group_lines(words);
+    if lines.is_empty() {
+        return String::new();
+    }
+    strip_gutter(&mut lines);
+    // a blank line showing only its line number is empty now, the gap to the next line keeps it
+    lines.retain(|line| !line.is_empty());
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    let char_width = estimate_char_width(&lines);
+    let line_pitch = estimate_line_pitch(&lines);
+    let origin = lines.iter().flatten().map(|word| word.bbox.x).min().unwrap_or(0);
+
+    let mut text = String::new();
+    let mut previous_center: Option<f64> = None;
+
+    for line in lines.iter() {
+        let center = line_center(line);
+
+        // keep blank lines, every additional line pitch of the gap is an empty line
+        if let Some(previous) = previous_center {
+            let line_breaks = ((center - previous) / line_pitch).round() as usize;
+            text.push_str(&"\n".repeat(line_breaks.max(1)));
+        }
+        previous_center = Some(center);
+
+        let mut previous_end: Option<i32> = None;
+        for word in line.iter() {
+            // the first word sets the indentation, later words keep their gap but at least one space
+            let spaces = match previous_end {
+                None => ((word.bbox.x - origin) as f64 / char_width).round() as usize,
+                Some(end) => (((word.bbox.x - end) as f64 / char_width).round() as usize).max(1),
+            };
+            text.push_str(&" ".repeat(spaces));
+            text.push_str(&word.text);
+            previous_end = Some(word.bbox.x + word.bbox.width);
+        }
+    }
+
+    text
+}
+
+/// Sorts the words into lines from top to bottom, the words of a line from left to right
+fn group_lines(words: &[Word]) -> Vec<Vec<Word>> {
+    let mut sorted: Vec<_> = words.iter().filter(|word| !word.text.trim().is_empty()).cloned().collect();
+    sorted.sort_by(|a, b| center_y(a).partial_cmp(&center_y(b)).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut lines: Vec<Vec<Word>> = Vec::new();
+    for word in sorted {
+        // a word belongs to the current line if its center lies within the height of the line
+        let same_line = lines.last().map(|line| {
+            let center = line_center(line);
+            let height = line.iter().map(|word| word.bbox.height).max().unwrap_or(0) as f64;
+            (center_y(&word) - center).abs() <= height / 2.
+        });
+
+        match (same_line, lines.last_mut()) {
+            (Some(true), Some(line)) => line.push(word),
+            _ => lines.push(vec![word]),
+        }
+    }
+
+    for line in lines.iter_mut() {
+        line.sort_by_key(|word| word.bbox.x);
+    }
+
+    lines
+}
+
+/// Removes editor line numbers, a gutter is assumed if most lines start with increasing numbers
+fn strip_gutter(lines: &mut [Vec<Word>]) {
+    if lines.len() < 2 {
+        return;
+    }
+
+    let numbers: Vec<Option<u32>> = lines.iter()
+        .map(|line| line.first().and_then(|word| word.text.parse::<u32>().ok()))
+        .collect();
+    let numbered = numbers.iter().flatten().count();
+    // a lone number can not be a gutter, the code of the line would be empty
+    let with_code = lines.iter().zip(numbers.iter()).filter(|(line, number)| number.is_some() && line.len() > 1).count();
+
+    let increasing = numbers.iter().flatten().collect::<Vec<_>>()
+        .windows(2)
+        .filter(|pair| pair[1] > pair[0])
+        .count();
+
+    if numbered < 2 || numbered * 2 < lines.len() || with_code == 0 || increasing * 5 < (numbered - 1) * 4 {
+        return;
+    }
+
+    for (line, number) in lines.iter_mut().zip(numbers.iter()) {
+        if number.is_some() {
+            line.remove(0);
+        }
+    }
+}
+
+/// Estimates the monospace character width, preferably from the distance of neighboring words
+/// as code mostly separates words by a single space, otherwise from the width of the words
+fn estimate_char_width(lines: &[Vec<Word>]) -> f64 {
+    let pitches: Vec<f64> = lines.iter()
+        .flat_map(|line| line.windows(2))
+        .map(|pair| (pair[1].bbox.x - pair[0].bbox.x) as f64 / (pair[0].text.chars().count() + 1) as f64)
+        .filter(|pitch| *pitch > 0.)
+        .collect();
+
+    if pitches.len() >= 3 {
+        return median(pitches).max(1.);
+    }
+
+    let widths = lines.iter()
+        .flatten()
+        .filter(|word| word.bbox.width > 0)
+        .map(|word| word.bbox.width as f64 / word.text.chars().count().max(1) as f64)
+        .collect();
+
+    median(widths).max(1.)
+}
+
+/// Estimates the distance of two lines, most neighboring lines are not separated by blank lines
+fn estimate_line_pitch(lines: &[Vec<Word>]) -> f64 {
+    let distances: Vec<f64> = lines.windows(2)
+        .map(|pair| line_center(&pair[1]) - line_center(&pair[0]))
+        .collect();
+
+    if distances.is_empty() {
+        return 1.;
+    }
+    median(distances).max(1.)
+}
+
+fn line_center(line: &[Word]) -> f64 {
+    line.iter().map(center_y).sum::<f64>() / line.len().max(1) as f64
+}
+
+fn center_y(word: &Word) -> f64 {
+    word.bbox.y as f64 + word.bbox.height as f64 / 2.
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    values[values.len() / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Word on a grid of 10 pixels per character and 20 pixels per line
+    fn word(text: &str, column: i32, row: i32) -> Word {
+        Word {
+            text: text.into(),
+            bbox: BoundingBox { x: column * 10, y: row * 20, width: text.len() as i32 * 10, height: 14 },
+            confidence: 1.,
+        }
+    }
+
+    #[test]
+    fn strips_gutter_of_numbered_blank_lines() {
+        let mut words = vec![
+            word("1", 0, 0), word("def", 3, 0), word("f(x):", 7, 0),
+            word("2", 0, 1), word("if", 7, 1), word("x:", 10, 1),
+            word("3", 0, 2), word("return", 11, 2), word("x", 18, 2),
+            word("8", 0, 7), word("return", 7, 7), word("0", 14, 7),
+        ];
+        // the blank lines only show their numbers
+        words.extend((4..8).map(|number| word(&number.to_string(), 0, number - 1)));
+
+        assert_eq!(reconstruct_layout(&words), "def f(x):\n    if x:\n        return x\n\n\n\n\n    return 0");
+    }
+
+    #[test]
+    fn keeps_numbers_without_gutter() {
+        let words = vec![
+            word("x", 0, 0), word("=", 2, 0), word("1", 4, 0),
+            word("2", 0, 1), word("*", 2, 1), word("x", 4, 1),
+        ];
+
+        assert_eq!(reconstruct_layout(&words), "x = 1\n2 * x");
+    }
+}
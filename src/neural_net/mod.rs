@@ -1 +1,10 @@
+pub(crate) mod debug;
+pub(crate) mod detector;
+pub(crate) mod heuristic;
+pub(crate) mod layout;
+pub(crate) mod ocr;
+pub(crate) mod pipeline;
+pub(crate) mod preprocessing;
+pub(crate) mod sampler;
+pub(crate) mod tracker;
 pub(crate) mod yolo;
\ No newline at end of file
@@ -0,0 +1,171 @@
+use crate::prelude::*;
+
+/// Recognized word, the bounding box is relative to the preprocessed, possibly upscaled region it was read from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Word {
+    pub text: String,
+    pub bbox: BoundingBox,
+    /// confidence of the OCR engine between 0 and 1
+    pub confidence: f32,
+}
+
+/// Recognized text of a region along with its words
+#[derive(Debug, Clone, Default)]
+pub struct OcrOutput {
+    pub text: String,
+    pub words: Vec<Word>,
+}
+
+/// Reads the text of a preprocessed region
+pub trait OcrEngine {
+    fn recognize(&mut self, img: &mut Mat) -> Result<OcrOutput, ExtractionError>;
+}
+
+/// Creates the configured OCR engine
+pub fn create_ocr(config: &OcrConfig) -> Result<Box<dyn OcrEngine>, ExtractionError> {
+    match config.engine {
+        OcrKind::Tesseract => Ok(Box::new(Tesseract::new(&config.language)?)),
+    }
+}
+
+pub struct Tesseract {
+    ocr: Ptr<dyn OCRTesseract>,
+}
+
+impl Tesseract {
+    pub fn new(language: &str) -> Result<Self, ExtractionError> {
+        let ocr = OCRTesseract::create("", language, "", OEM_DEFAULT, PSM_SINGLE_BLOCK)
+            .map_err(|err| ExtractionError::Ocr(err.to_string()))?;
+
+        Ok(Self { ocr: ocr })
+    }
+}
+
+impl OcrEngine for Tesseract {
+    fn recognize(&mut self, img: &mut Mat) -> Result<OcrOutput, ExtractionError> {
+        let mut text = String::new();
+        let mut rects = VectorOfRect::new();
+        let mut texts = VectorOfString::new();
+        let mut confidences = VectorOff32::new();
+
+        self.ocr.run_multiple(img, &mut text, &mut rects, &mut texts, &mut confidences, OCR_LEVEL_WORD)
+            .map_err(|err| ExtractionError::Ocr(err.to_string()))?;
+
+        let mut words = Vec::with_capacity(texts.len());
+        for (idx, word) in texts.iter().enumerate() {
+            let rect = rects.get(idx)?;
+            words.push(Word {
+                text: word,
+                bbox: BoundingBox { x: rect.x, y: rect.y, width: rect.width, height: rect.height },
+                // tesseract reports confidences in percent
+                confidence: confidences.get(idx)? / 100.,
+            });
+        }
+
+        Ok(OcrOutput {
+            text: text,
+            words: words,
+        })
+    }
+}
+
+/// Deterministic engine for tests without Tesseract, every region reads as the given text.
+/// The words are laid out on a monospace grid of `CHAR_WIDTH` by `LINE_HEIGHT` pixels with full confidence.
+#[cfg(test)]
+pub struct MockOcr {
+    text: String,
+}
+
+#[cfg(test)]
+impl MockOcr {
+    pub const CHAR_WIDTH: i32 = 10;
+    pub const LINE_HEIGHT: i32 = 20;
+
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl OcrEngine for MockOcr {
+    fn recognize(&mut self, _img: &mut Mat) -> Result<OcrOutput, ExtractionError> {
+        let mut words = Vec::new();
+
+        for (line_idx, line) in self.text.lines().enumerate() {
+            for (start, word) in split_words(line) {
+                let column = line[..start].chars().count();
+                let length = word.chars().count();
+
+                words.push(Word {
+                    text: word.into(),
+                    bbox: BoundingBox {
+                        x: column as i32 * Self::CHAR_WIDTH,
+                        y: line_idx as i32 * Self::LINE_HEIGHT,
+                        width: length as i32 * Self::CHAR_WIDTH,
+                        height: Self::LINE_HEIGHT,
+                    },
+                    confidence: 1.,
+                });
+            }
+        }
+
+        Ok(OcrOutput {
+            text: self.text.clone(),
+            words: words,
+        })
+    }
+}
+
+/// Returns the whitespace separated words of the line along with their byte offsets
+#[cfg(test)]
+fn split_words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (idx, c) in line.char_indices() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(idx),
+            (true, Some(begin)) => {
+                words.push((begin, &line[begin..idx]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        words.push((begin, &line[begin..]));
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = "def f(x):\n    if x:\n        return x\n\n    return 0";
+
+    #[test]
+    fn mock_lays_out_words_on_grid() {
+        let output = MockOcr::new("int main() {\n    return 0;\n}").recognize(&mut Mat::default()).unwrap();
+        let words: Vec<_> = output.words.iter().map(|word| (word.text.as_str(), word.bbox.x, word.bbox.y, word.bbox.width)).collect();
+
+        assert_eq!(words, vec![
+            ("int", 0, 0, 30),
+            ("main()", 40, 0, 60),
+            ("{", 110, 0, 10),
+            ("return", 40, 20, 60),
+            ("0;", 110, 20, 20),
+            ("}", 0, 40, 10),
+        ]);
+    }
+
+    #[test]
+    fn layout_of_mock_words_restores_indentation() {
+        let output = MockOcr::new(PROGRAM).recognize(&mut Mat::default()).unwrap();
+
+        assert_eq!(reconstruct_layout(&output.words), PROGRAM);
+    }
+}
@@ -0,0 +1,422 @@
+use crate::prelude::*;
+
+/// Entry points of the frame analysis
+pub struct Pipeline;
+
+impl Pipeline {
+    /// Analyzes the sampled frames of the video on the worker pool, the results reach the parser in time order
+    pub fn run(sender: SyncSender<(Message, i32)>, file: &str, config: &Config) -> Result<(), ExtractionError> {
+        let path = file.to_string();
+        let sampling = config.sampling.clone();
+
+        run_pipeline(sender, config, file, move |mut queue: FrameQueue| {
+            // initialize video capture 
+            let mut video_capture = VideoCapture::from_file(
+                &path, 
+                videoio::CAP_ANY
+            )?;
+
+            // select the analyzed frames by their time, which is independent of the frame rate
+            let mut sampler = FrameSampler::new(&sampling);
+            let interval = sampler.interval_ms();
+            let end = sampling.end_seconds.map(|end| end * 1000.).unwrap_or(f64::INFINITY);
+            let mut target = sampling.start_seconds.max(0.) * 1000.;
+            let mut position = 0.;
+
+            // start video processing
+            while target <= end {
+                // seek over long gaps, decode short ones as seeking is slower than decoding a few frames
+                if target - position > sampling.seek_threshold_ms {
+                    video_capture.set(CAP_PROP_POS_MSEC, target)?;
+                }
+
+                // extract the first frame at or after the target, break loop if video capture has no more frames
+                let mut grabbed = video_capture.grab()?;
+                position = video_capture.get(CAP_PROP_POS_MSEC)?;
+                while grabbed && position < target {
+                    grabbed = video_capture.grab()?;
+                    position = video_capture.get(CAP_PROP_POS_MSEC)?;
+                }
+                if !grabbed || position > end {
+                    break
+                }
+
+                let mut img = Mat::default();
+                video_capture.retrieve(&mut img, 0)?;
+                target = position + interval;
+
+                // skip detection and OCR on frames the sampler rejects, stop if the pipeline stopped
+                if sampler.sample(&img, position)? && !queue.push((position / 1000.) as i32, img) {
+                    break
+                }
+            }
+            println!("Video processing finished");
+            Ok(())
+        })
+    }
+
+    /// Runs detection and OCR on single images, the time code of a message is the index of its image
+    pub fn run_images(sender: SyncSender<(Message, i32)>, files: &[String], config: &Config) -> Result<(), ExtractionError> {
+        let directory = files.first().and_then(|file| Path::new(file).parent()).unwrap_or_else(|| Path::new("images"));
+        let files = files.to_vec();
+
+        // every image is a different slide, so its regions get new ids, even if they show code at the same place
+        let mut config = config.clone();
+        config.tracking.enabled = false;
+
+        run_pipeline(sender, &config, &directory.display().to_string(), move |mut queue: FrameQueue| {
+            for (idx, file) in files.iter().enumerate() {
+                let img = imread(file, IMREAD_COLOR)?;
+
+                if img.empty()? {
+                    eprintln!("Error: could not read image {}", file);
+                    continue;
+                }
+                if !queue.push(idx as i32, img) {
+                    break
+                }
+            }
+            println!("Image processing finished");
+            Ok(())
+        })
+    }
+}
+
+/// Code detector and OCR engine of a single worker
+pub struct FrameReader {
+    detector: Box<dyn CodeDetector>,
+    ocr: Box<dyn OcrEngine>,
+}
+
+impl FrameReader {
+    pub fn new(config: &Config) -> Result<Self, ExtractionError> {
+        Ok(Self {
+            detector: create_detector(&config.detection)?,
+            ocr: create_ocr(&config.ocr)?,
+        })
+    }
+
+    /// Reader of the given engines, e.g. of deterministic ones in tests
+    #[cfg(test)]
+    pub fn with_engines(detector: Box<dyn CodeDetector>, ocr: Box<dyn OcrEngine>) -> Self {
+        Self {
+            detector: detector,
+            ocr: ocr,
+        }
+    }
+
+    /// Detects and reads every source code region in the frame
+    pub fn read(&mut self, frame: Frame, config: &Config) -> Result<FrameResult, ExtractionError> {
+        let img = frame.img;
+        let detections = self.detector.detect(&img)?;
+        let mut regions = extract_regions(&img, detections, config)?;
+        let mut texts = Vec::with_capacity(regions.len());
+
+        for (region, src) in regions.iter_mut() {
+            if src.empty()? {
+                continue;
+            }
+            let mut ocr_output = self.ocr.recognize(src)?;
+
+            // the flat text of the engine loses the indentation, which is rebuilt from the words
+            if config.ocr.reconstruct_layout && !ocr_output.words.is_empty() {
+                ocr_output.text = reconstruct_layout(&ocr_output.words);
+            }
+            texts.push((region.clone(), ocr_output));
+        }
+
+        // the boxes are only drawn for the preview and the debug artifacts, after all regions were cropped
+        let frame_img = if config.preview || config.debug_dir.is_some() {
+            draw_regions(&img, &regions)?
+        } else {
+            img
+        };
+
+        Ok(FrameResult {
+            seq: frame.seq,
+            time_code: frame.time_code,
+            frame: frame_img,
+            crops: regions.into_iter().map(|(_, src)| src).collect(),
+            texts: texts,
+        })
+    }
+}
+
+/// Sampled frame waiting for detection and OCR, the sequence number restores the time order
+pub struct Frame {
+    pub seq: usize,
+    pub time_code: i32,
+    pub img: Mat,
+}
+
+/// Detected regions of a frame along with their recognized texts
+pub struct FrameResult {
+    pub seq: usize,
+    pub time_code: i32,
+    /// frame with the predicted bounding boxes drawn in if it is previewed or dumped
+    pub frame: Mat,
+    /// preprocessed sub images of the regions
+    pub crops: Vec<Mat>,
+    pub texts: Vec<(Region, OcrOutput)>,
+}
+
+/// Hands the sampled frames to the workers, blocks while too many frames are in flight
+pub struct FrameQueue {
+    seq: usize,
+    jobs: SyncSender<Frame>,
+    credits: Receiver<()>,
+}
+
+impl FrameQueue {
+    /// Returns false if the pipeline stopped and does not accept frames anymore
+    pub fn push(&mut self, time_code: i32, img: Mat) -> bool {
+        if self.credits.recv().is_err() {
+            return false;
+        }
+        let frame = Frame {
+            seq: self.seq,
+            time_code: time_code,
+            img: img,
+        };
+        self.seq += 1;
+
+        self.jobs.send(frame).is_ok()
+    }
+}
+
+/// Decodes frames on its own thread, detects and reads them on the worker pool and delivers the results in time order.
+/// A frame only takes a credit from the queue once it is delivered, so at most `queue_size` frames are in flight.
+pub fn run_pipeline<D>(sender: SyncSender<(Message, i32)>, config: &Config, name: &str, decode: D) -> Result<(), ExtractionError>
+where
+    D: FnOnce(FrameQueue) -> Result<(), ExtractionError> + Send + 'static,
+{
+    let config = Arc::new(config.clone());
+    let workers = config.pipeline.workers.max(1);
+    let capacity = config.pipeline.queue_size.max(workers);
+
+    let (job_sender, job_receiver) = mpsc::sync_channel::<Frame>(capacity);
+    let (result_sender, result_receiver) = mpsc::sync_channel::<Result<FrameResult, ExtractionError>>(capacity);
+    let (credit_sender, credit_receiver) = mpsc::sync_channel::<()>(capacity);
+    for _ in 0..capacity {
+        credit_sender.send(())?;
+    }
+
+    // every worker creates its own detector and OCR engine
+    let job_receiver = Arc::new(Mutex::new(job_receiver));
+    let handles: Vec<_> = (0..workers)
+        .map(|_| {
+            let jobs = job_receiver.clone();
+            let results = result_sender.clone();
+            let config = config.clone();
+            thread::spawn(move || work(jobs, results, &config))
+        })
+        .collect();
+    drop(result_sender);
+
+    let decoder = thread::spawn(move || decode(FrameQueue { seq: 0, jobs: job_sender, credits: credit_receiver }));
+
+    // dropping the credits and results on return stops the decoder and the workers
+    let collected = collect(&sender, result_receiver, credit_sender, &config, name);
+
+    let decoded = decoder.join().map_err(|_| ExtractionError::Detection("decoder thread panicked".into()))?;
+    for handle in handles {
+        handle.join().map_err(|_| ExtractionError::Detection("worker thread panicked".into()))?;
+    }
+    collected?;
+    decoded?;
+
+    sender.send((Message::EndMessage, 0))?;
+    Ok(())
+}
+
+fn work(jobs: Arc<Mutex<Receiver<Frame>>>, results: SyncSender<Result<FrameResult, ExtractionError>>, config: &Config) {
+    let mut reader = match FrameReader::new(config) {
+        Ok(reader) => reader,
+        Err(err) => {
+            results.send(Err(err)).ok();
+            return;
+        }
+    };
+
+    loop {
+        // the lock is only held while waiting for the next frame
+        let frame = match jobs.lock() {
+            Ok(jobs) => jobs.recv(),
+            Err(_) => break,
+        };
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(_) => break,
+        };
+
+        if results.send(reader.read(frame, config)).is_err() {
+            break;
+        }
+    }
+}
+
+/// Reorders the results, a result is held back until all earlier frames are delivered.
+/// Tracking runs on the ordered results, as the workers finish their frames in any order.
+fn collect(
+    sender: &SyncSender<(Message, i32)>,
+    results: Receiver<Result<FrameResult, ExtractionError>>,
+    credits: SyncSender<()>,
+    config: &Config,
+    name: &str
+) -> Result<(), ExtractionError> {
+    let mut debug = DebugDump::from_config(config, name)?;
+    let mut tracker = RegionTracker::new(&config.tracking);
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+
+    for result in results.iter() {
+        let result = result?;
+        pending.insert(result.seq, result);
+
+        while let Some(mut result) = pending.remove(&next) {
+            next += 1;
+            let texts = tracker.update(result.time_code, &mut result.texts);
+            deliver(sender, &result, texts, tracker.ended(), debug.as_mut())?;
+
+            if config.preview && show_preview(&result.frame)? {
+                println!("Processing stopped");
+                return Ok(());
+            }
+            // the decoder may have finished already
+            credits.send(()).ok();
+        }
+    }
+
+    // the regions on screen at the end could not be confirmed anymore
+    for (time_code, region, ocr_output) in tracker.finish() {
+        sender.send((Message::StreamMessage(ocr_output.text, region), time_code))?;
+    }
+
+    Ok(())
+}
+
+/// Sends the tracked texts and the debug artifacts of the frame to the parser
+fn deliver(
+    sender: &SyncSender<(Message, i32)>,
+    result: &FrameResult,
+    texts: Vec<(i32, Region, OcrOutput)>,
+    ended: Vec<usize>,
+    debug: Option<&mut DebugDump>
+) -> Result<(), ExtractionError> {
+    let sample = match debug {
+        Some(debug) => {
+            Some(debug.write_sample(result.time_code, &result.frame, &result.crops, &result.texts)?)
+        }
+        None => None,
+    };
+
+    for (time_code, region, ocr_output) in texts {
+        sender.send((Message::StreamMessage(ocr_output.text, region), time_code))?;
+    }
+    if !ended.is_empty() {
+        sender.send((Message::TrackEndMessage(ended), result.time_code))?;
+    }
+    // send artifacts after the text, so the sample is lexed with the classified language
+    if let Some(sample) = sample {
+        sender.send((Message::DebugMessage(sample), result.time_code))?;
+    }
+
+    Ok(())
+}
+
+/// Shows the frame with its detections, returns true if processing should be stopped
+fn show_preview(img: &Mat) -> Result<bool, ExtractionError> {
+    highgui::imshow("preview", img)?;
+    let key = highgui::wait_key(1)?;
+
+    Ok(key == 27 || key == 'q' as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Detects the same regions in every frame
+    struct FixedDetector(Vec<(Rect, f32)>);
+
+    impl CodeDetector for FixedDetector {
+        fn detect(&mut self, _img: &Mat) -> Result<Vec<(Rect, f32)>, ExtractionError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    const PROGRAM: &str = "int main() {\n    return 0;\n}";
+
+    fn region(id: usize) -> Region {
+        Region {
+            id: id,
+            bbox: BoundingBox { x: 10, y: 10, width: 150, height: 100 },
+            confidence: 0.9,
+        }
+    }
+
+    fn read(detections: Vec<(Rect, f32)>, config: &Config) -> FrameResult {
+        let mut reader = FrameReader::with_engines(Box::new(FixedDetector(detections)), Box::new(MockOcr::new(PROGRAM)));
+        let img = Mat::new_rows_cols_with_default(120, 400, opencv::core::CV_8UC3, Scalar::all(255.)).unwrap();
+
+        reader.read(Frame { seq: 3, time_code: 7, img: img }, config).unwrap()
+    }
+
+    #[test]
+    fn reads_every_detected_region() {
+        let config = Config::default();
+        let result = read(vec![(Rect::new(10, 10, 150, 100), 0.9), (Rect::new(200, 10, 150, 100), 0.8)], &config);
+
+        assert_eq!((result.seq, result.time_code), (3, 7));
+        assert_eq!(result.crops.len(), 2);
+        assert_eq!(result.texts.len(), 2);
+        for (region, ocr_output) in result.texts.iter() {
+            assert_eq!(ocr_output.text, PROGRAM);
+            assert_eq!(ocr_output.words.len(), 6);
+            assert!(region.confidence >= 0.8);
+        }
+
+        // the regions are padded by the configured amount
+        let bbox = &result.texts[0].0.bbox;
+        assert_eq!((bbox.x, bbox.y, bbox.width, bbox.height), (6, 8, 158, 104));
+    }
+
+    #[test]
+    fn skips_detections_outside_of_frame() {
+        let config = Config::default();
+        let result = read(vec![(Rect::new(500, 10, 50, 50), 0.9)], &config);
+
+        assert!(result.texts.is_empty());
+    }
+
+    #[test]
+    fn keeps_engine_text_without_layout_reconstruction() {
+        let mut config = Config::default();
+        config.ocr.reconstruct_layout = false;
+        let result = read(vec![(Rect::new(10, 10, 150, 100), 0.9)], &config);
+
+        assert_eq!(result.texts[0].1.text, PROGRAM);
+    }
+
+    #[test]
+    fn single_static_frame_yields_knowledge_components() {
+        let config = Config::default();
+        let (result_sender, results) = mpsc::sync_channel(1);
+        let (credits, _credit_receiver) = mpsc::sync_channel(1);
+        let (sender, receiver) = mpsc::sync_channel(8);
+
+        // the sampler skips the unchanged frames after it, so the region is seen only once
+        let ocr_output = OcrOutput { text: PROGRAM.into(), words: Vec::new() };
+        let result = FrameResult { seq: 0, time_code: 0, frame: Mat::default(), crops: Vec::new(), texts: vec![(region(0), ocr_output)] };
+        result_sender.send(Ok(result)).unwrap();
+        drop(result_sender);
+
+        collect(&sender, results, credits, &config, "static").unwrap();
+        sender.send((Message::EndMessage, 0)).unwrap();
+        let metadata = VideoMetadata::new("C programming", "v.mp4", None);
+        let (router, _) = parse_knowledge_components(receiver, metadata, Locator::File("v.mp4".into()), Arc::new(config));
+
+        assert_eq!(router.language(0), Some(ProgrammingLanguage::C));
+        assert!(!router.knowledge_components()["c"].is_empty());
+    }
+}
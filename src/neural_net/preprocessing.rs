@@ -0,0 +1,166 @@
+use crate::prelude::*;
+
+/// Prepares a grayscale region for OCR. Dark themes are inverted to dark text on a light background,
+/// afterwards the steps configured for the theme of the region are applied in order.
+pub fn preprocess(gray: &Mat, config: &PreprocessingConfig) -> Result<Mat, ExtractionError> {
+    let mut img = Mat::default();
+    let mean_value = opencv::core::mean(gray, &no_array()?)?;
+    let dark = mean_value.get(0).map(|value| *value <= config.inverse_threshold).unwrap_or(false);
+
+    if dark {
+        subtract(&Scalar::all(255.), gray, &mut img, &no_array()?, -1)?;
+    } else {
+        gray.copy_to(&mut img)?;
+    }
+
+    let steps = if dark { &config.dark_theme } else { &config.light_theme };
+    for step in steps.iter() {
+        img = match step {
+            PreprocessingStep::Upscale => upscale(&img, config)?,
+            PreprocessingStep::Denoise => denoise(&img, config)?,
+            PreprocessingStep::Sharpen => sharpen(&img, config)?,
+            PreprocessingStep::Deskew => deskew(&img, config)?,
+            PreprocessingStep::Otsu => otsu(&img)?,
+            PreprocessingStep::Adaptive => adaptive(&img, config)?,
+        };
+    }
+
+    Ok(img)
+}
+
+/// Enlarges narrow regions, Tesseract needs characters of about 20 pixels height
+fn upscale(img: &Mat, config: &PreprocessingConfig) -> Result<Mat, ExtractionError> {
+    if img.cols() <= 0 || img.cols() >= config.upscale_width {
+        return Ok(img.try_clone()?);
+    }
+
+    let factor = (config.upscale_width as f64 / img.cols() as f64).min(config.max_upscale);
+    let mut dst = Mat::default();
+    resize(img, &mut dst, Size::new(0, 0), factor, factor, INTER_CUBIC)?;
+
+    Ok(dst)
+}
+
+/// Removes the block and ringing artifacts of compressed video
+fn denoise(img: &Mat, config: &PreprocessingConfig) -> Result<Mat, ExtractionError> {
+    let mut dst = Mat::default();
+    fast_nl_means_denoising(img, &mut dst, config.denoise_strength, 7, 21)?;
+
+    Ok(dst)
+}
+
+/// Unsharp masking, adds the difference to a blurred copy to restore the edges of blurry characters
+fn sharpen(img: &Mat, config: &PreprocessingConfig) -> Result<Mat, ExtractionError> {
+    let mut blurred = Mat::default();
+    gaussian_blur(img, &mut blurred, Size::new(0, 0), 1.5, 0., BORDER_DEFAULT)?;
+
+    let mut dst = Mat::default();
+    add_weighted(img, 1. + config.sharpen_amount, &blurred, -config.sharpen_amount, 0., &mut dst, -1)?;
+
+    Ok(dst)
+}
+
+/// Rotates a slightly tilted region, e.g. a filmed screen, so its lines become horizontal
+fn deskew(img: &Mat, config: &PreprocessingConfig) -> Result<Mat, ExtractionError> {
+    // the text is dark after the inversion, so the inverted binarization marks the characters
+    let mut ink = Mat::default();
+    threshold(img, &mut ink, 0., 255., THRESH_BINARY_INV | THRESH_OTSU)?;
+
+    let mut points = Mat::default();
+    find_non_zero(&ink, &mut points)?;
+    if points.rows() < 2 {
+        return Ok(img.try_clone()?);
+    }
+
+    // the angle of the minimal rectangle around the characters is reported within 90 degrees
+    let mut angle = min_area_rect(&points)?.angle() as f64;
+    if angle < -45. {
+        angle += 90.;
+    } else if angle > 45. {
+        angle -= 90.;
+    }
+
+    // larger angles are rather caused by ragged lines than by a tilted screen
+    if angle.abs() < 0.1 || angle.abs() > config.max_skew_degrees {
+        return Ok(img.try_clone()?);
+    }
+
+    let center = Point2f::new(img.cols() as f32 / 2., img.rows() as f32 / 2.);
+    let rotation = get_rotation_matrix_2d(center, angle, 1.)?;
+    let mut dst = Mat::default();
+    warp_affine(img, &mut dst, &rotation, img.size()?, INTER_CUBIC, BORDER_REPLICATE, Scalar::all(0.))?;
+
+    Ok(dst)
+}
+
+fn otsu(img: &Mat) -> Result<Mat, ExtractionError> {
+    let mut dst = Mat::default();
+    threshold(img, &mut dst, 0., 255., THRESH_BINARY | THRESH_OTSU)?;
+
+    Ok(dst)
+}
+
+/// Binarizes by the neighborhood of every pixel, which copes with gradients and highlighted lines
+fn adaptive(img: &Mat, config: &PreprocessingConfig) -> Result<Mat, ExtractionError> {
+    // the block size has to be odd and larger than one
+    let block_size = config.adaptive_block_size.max(3) | 1;
+    let mut dst = Mat::default();
+    adaptive_threshold(img, &mut dst, 255., ADAPTIVE_THRESH_GAUSSIAN_C, THRESH_BINARY, block_size, config.adaptive_offset)?;
+
+    Ok(dst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Region of a single text block on a background
+    fn region(width: i32, background: f64, text: f64) -> Mat {
+        let mut img = Mat::new_rows_cols_with_default(20, width, opencv::core::CV_8UC1, Scalar::all(background)).unwrap();
+        rectangle(&mut img, Rect::new(width / 4, 5, width / 2, 10), Scalar::all(text), -1, LINE_8, 0).unwrap();
+        img
+    }
+
+    fn config(dark_theme: Vec<PreprocessingStep>, light_theme: Vec<PreprocessingStep>) -> PreprocessingConfig {
+        PreprocessingConfig { dark_theme, light_theme, upscale_width: 80, ..PreprocessingConfig::default() }
+    }
+
+    fn binary(img: &Mat) -> bool {
+        img.data_typed::<u8>().unwrap().iter().all(|value| *value == 0 || *value == 255)
+    }
+
+    #[test]
+    fn default_chain_only_upscales() {
+        let config = PreprocessingConfig::default();
+
+        assert_eq!(config.dark_theme, vec![PreprocessingStep::Upscale]);
+        assert_eq!(config.light_theme, vec![PreprocessingStep::Upscale]);
+    }
+
+    #[test]
+    fn steps_run_in_the_configured_order() {
+        let img = region(40, 220., 30.);
+
+        // the interpolation of the upscaling softens the edges of a binarized region
+        let binarized_last = preprocess(&img, &config(Vec::new(), vec![PreprocessingStep::Upscale, PreprocessingStep::Otsu])).unwrap();
+        assert_eq!(binarized_last.cols(), 80);
+        assert!(binary(&binarized_last));
+
+        let upscaled_last = preprocess(&img, &config(Vec::new(), vec![PreprocessingStep::Otsu, PreprocessingStep::Upscale])).unwrap();
+        assert_eq!(upscaled_last.cols(), 80);
+        assert!(!binary(&upscaled_last));
+    }
+
+    #[test]
+    fn every_theme_runs_its_own_steps() {
+        let config = config(Vec::new(), vec![PreprocessingStep::Upscale]);
+
+        let light = preprocess(&region(40, 220., 30.), &config).unwrap();
+        assert_eq!(light.cols(), 80);
+
+        // dark themes are inverted, but their steps are switched off
+        let dark = preprocess(&region(40, 30., 220.), &config).unwrap();
+        assert_eq!(dark.cols(), 40);
+        assert_eq!(*dark.at_2d::<u8>(0, 0).unwrap(), 225);
+    }
+}
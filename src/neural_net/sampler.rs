@@ -0,0 +1,124 @@
+use crate::prelude::*;
+
+/// Selects the frames that are analyzed, unchanged frames are skipped before detection and OCR
+pub struct FrameSampler {
+    pub config: SamplingConfig,
+    /// downscaled grayscale copy of the last analyzed frame
+    pub previous: Option<Mat>,
+    /// position of the last analyzed frame in milliseconds
+    pub analyzed_ms: Option<f64>,
+}
+
+impl FrameSampler {
+    /// Minimal distance of two candidate frames, frames of a shorter distance are not distinguishable anyway
+    const MIN_INTERVAL_MS: f64 = 10.;
+
+    pub fn new(config: &SamplingConfig) -> Self {
+        Self {
+            config: config.clone(),
+            previous: None,
+            analyzed_ms: None,
+        }
+    }
+
+    /// Returns the time between two candidate frames in milliseconds
+    pub fn interval_ms(&self) -> f64 {
+        let interval = match self.config.mode {
+            SamplingMode::Fixed => self.config.delay_seconds * 1000.,
+            SamplingMode::Change => self.config.check_interval_ms,
+        };
+
+        interval.max(Self::MIN_INTERVAL_MS)
+    }
+
+    /// Returns true if the candidate frame at the position in milliseconds should be analyzed
+    pub fn sample(&mut self, img: &Mat, position_ms: f64) -> Result<bool, ExtractionError> {
+        match self.config.mode {
+            SamplingMode::Fixed => Ok(true),
+            SamplingMode::Change => {
+                let thumbnail = self.thumbnail(img)?;
+                let changed = match &self.previous {
+                    Some(previous) => self.changed_ratio(previous, &thumbnail)? > self.config.change_ratio,
+                    None => true,
+                };
+                let overdue = self.analyzed_ms.is_none_or(|analyzed| position_ms - analyzed >= self.config.max_gap_ms);
+
+                // compare against the last analyzed frame, so slow edits add up until they are analyzed
+                if changed || overdue {
+                    self.previous = Some(thumbnail);
+                    self.analyzed_ms = Some(position_ms);
+                }
+                Ok(changed || overdue)
+            }
+        }
+    }
+
+    fn thumbnail(&self, img: &Mat) -> Result<Mat, ExtractionError> {
+        let mut gray = Mat::default();
+        let mut thumbnail = Mat::default();
+        let width = self.config.compare_width.min(img.cols()).max(1);
+        let height = (img.rows() * width / img.cols().max(1)).max(1);
+
+        cvt_color(img, &mut gray, COLOR_BGR2GRAY, 0)?;
+        resize(&gray, &mut thumbnail, Size::new(width, height), 0., 0., INTER_AREA)?;
+
+        Ok(thumbnail)
+    }
+
+    /// Returns the fraction of pixels whose intensity changed by more than the pixel threshold
+    fn changed_ratio(&self, previous: &Mat, current: &Mat) -> Result<f64, ExtractionError> {
+        if previous.rows() != current.rows() || previous.cols() != current.cols() {
+            return Ok(1.);
+        }
+        let mut diff = Mat::default();
+        let mut mask = Mat::default();
+
+        absdiff(previous, current, &mut diff)?;
+        threshold(&diff, &mut mask, self.config.pixel_threshold, 255., THRESH_BINARY)?;
+        let changed = count_non_zero(&mask)?;
+
+        Ok(changed as f64 / (current.rows() * current.cols()).max(1) as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(intensity: f64) -> Mat {
+        Mat::new_rows_cols_with_default(90, 160, opencv::core::CV_8UC3, Scalar::all(intensity)).unwrap()
+    }
+
+    #[test]
+    fn unchanged_frames_are_skipped_until_the_max_gap() {
+        let mut sampler = FrameSampler::new(&SamplingConfig::default());
+        let white = frame(255.);
+
+        assert!(sampler.sample(&white, 0.).unwrap());
+        assert!(!sampler.sample(&white, 250.).unwrap());
+        assert!(!sampler.sample(&white, 750.).unwrap());
+        // a static screen is analyzed again, so its regions are confirmed by the tracker
+        assert!(sampler.sample(&white, 1000.).unwrap());
+        assert!(!sampler.sample(&white, 1250.).unwrap());
+    }
+
+    #[test]
+    fn changed_frame_is_analyzed() {
+        let mut sampler = FrameSampler::new(&SamplingConfig::default());
+
+        assert!(sampler.sample(&frame(255.), 0.).unwrap());
+        assert!(sampler.sample(&frame(0.), 250.).unwrap());
+        assert!(!sampler.sample(&frame(0.), 500.).unwrap());
+    }
+
+    #[test]
+    fn fixed_sampling_analyzes_every_frame() {
+        let config = SamplingConfig { mode: SamplingMode::Fixed, ..SamplingConfig::default() };
+        let mut sampler = FrameSampler::new(&config);
+        let white = frame(255.);
+
+        assert!(sampler.sample(&white, 0.).unwrap());
+        assert!(sampler.sample(&white, 1000.).unwrap());
+        assert_eq!(sampler.interval_ms(), 1000.);
+    }
+}
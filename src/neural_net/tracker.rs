@@ -0,0 +1,262 @@
+use crate::prelude::*;
+
+/// Follows the code regions across frames, so an editor or terminal keeps its id while it stays on screen.
+/// Regions are matched to tracks by the overlap with the last box of a track, so a track follows a moving or resized region,
+/// and a new track is only reported once it was seen in `min_hits` consecutive frames.
+pub struct RegionTracker {
+    config: TrackingConfig,
+    tracks: Vec<Track>,
+    next_id: usize,
+    /// ids of the tracks ended since the last call of `ended`
+    ended: Vec<usize>,
+}
+
+struct Track {
+    id: usize,
+    /// x, y, width and height of the last detection
+    bbox: [f64; 4],
+    hits: usize,
+    misses: usize,
+    /// texts of an unconfirmed track, delivered once it is confirmed or the video ends and dropped if it disappears before
+    pending: Vec<(i32, Region, OcrOutput)>,
+}
+
+impl RegionTracker {
+    pub fn new(config: &TrackingConfig) -> Self {
+        Self {
+            config: config.clone(),
+            tracks: Vec::new(),
+            next_id: 0,
+            ended: Vec::new(),
+        }
+    }
+
+    /// Replaces the ids of the regions of the next frame by those of their tracks and returns the texts to deliver in time order
+    pub fn update(&mut self, time_code: i32, texts: &mut [(Region, OcrOutput)]) -> Vec<(i32, Region, OcrOutput)> {
+        // without tracking every region is new, reported right away and ends with its frame
+        if !self.config.enabled {
+            return texts.iter_mut()
+                .map(|(region, ocr_output)| {
+                    region.id = self.next_id;
+                    self.ended.push(region.id);
+                    self.next_id += 1;
+                    (time_code, region.clone(), ocr_output.clone())
+                })
+                .collect();
+        }
+
+        let assignments = self.assign(texts);
+        let mut matched = Vec::with_capacity(texts.len());
+        let mut delivered = Vec::new();
+
+        for ((region, ocr_output), assignment) in texts.iter_mut().zip(assignments) {
+            let track_idx = match assignment {
+                Some(track_idx) => {
+                    self.tracks[track_idx].observe(&region.bbox);
+                    track_idx
+                }
+                None => {
+                    self.tracks.push(Track::new(self.next_id, &region.bbox));
+                    self.next_id += 1;
+                    self.tracks.len() - 1
+                }
+            };
+            matched.push(track_idx);
+
+            let track = &mut self.tracks[track_idx];
+            region.id = track.id;
+            track.pending.push((time_code, region.clone(), ocr_output.clone()));
+
+            if track.hits >= self.config.min_hits {
+                delivered.append(&mut track.pending);
+            }
+        }
+
+        // a confirmed track survives a few misses, e.g. an editor hidden by a slide, an unconfirmed one none
+        let (min_hits, max_misses) = (self.config.min_hits, self.config.max_misses);
+        for (idx, track) in self.tracks.iter_mut().enumerate() {
+            if !matched.contains(&idx) {
+                track.misses += 1;
+            }
+        }
+        let ended = &mut self.ended;
+        self.tracks.retain(|track| {
+            let alive = track.misses == 0 || (track.hits >= min_hits && track.misses <= max_misses);
+            if !alive {
+                ended.push(track.id);
+            }
+            alive
+        });
+
+        delivered.sort_by_key(|(time_code, _, _)| *time_code);
+        delivered
+    }
+
+    /// Returns the texts of the unconfirmed tracks still on screen in time order, e.g. of the code shown last
+    pub fn finish(&mut self) -> Vec<(i32, Region, OcrOutput)> {
+        let mut delivered: Vec<_> = self.tracks.iter_mut().flat_map(|track| track.pending.drain(..)).collect();
+        delivered.sort_by_key(|(time_code, _, _)| *time_code);
+        delivered
+    }
+
+    /// Returns the ids of the tracks ended since the last call, no further texts are delivered for them
+    pub fn ended(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.ended)
+    }
+
+    /// Greedily matches the regions to the tracks with the largest overlap first
+    fn assign(&self, texts: &[(Region, OcrOutput)]) -> Vec<Option<usize>> {
+        let mut pairs = Vec::new();
+        for (region_idx, (region, _)) in texts.iter().enumerate() {
+            for (track_idx, track) in self.tracks.iter().enumerate() {
+                let overlap = iou(&to_array(&region.bbox), &track.bbox);
+                if overlap >= self.config.iou_threshold {
+                    pairs.push((overlap, region_idx, track_idx));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut assignments = vec![None; texts.len()];
+        let mut used = vec![false; self.tracks.len()];
+        for (_, region_idx, track_idx) in pairs {
+            if assignments[region_idx].is_none() && !used[track_idx] {
+                assignments[region_idx] = Some(track_idx);
+                used[track_idx] = true;
+            }
+        }
+
+        assignments
+    }
+}
+
+impl Track {
+    fn new(id: usize, bbox: &BoundingBox) -> Self {
+        Self {
+            id: id,
+            bbox: to_array(bbox),
+            hits: 1,
+            misses: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    fn observe(&mut self, bbox: &BoundingBox) {
+        self.bbox = to_array(bbox);
+        self.hits += 1;
+        self.misses = 0;
+    }
+}
+
+fn to_array(bbox: &BoundingBox) -> [f64; 4] {
+    [bbox.x as f64, bbox.y as f64, bbox.width as f64, bbox.height as f64]
+}
+
+/// Intersection over union of two boxes given as x, y, width and height
+fn iou(a: &[f64; 4], b: &[f64; 4]) -> f64 {
+    let width = (a[0] + a[2]).min(b[0] + b[2]) - a[0].max(b[0]);
+    let height = (a[1] + a[3]).min(b[1] + b[3]) - a[1].max(b[1]);
+    if width <= 0. || height <= 0. {
+        return 0.;
+    }
+
+    let intersection = width * height;
+    let union = a[2] * a[3] + b[2] * b[3] - intersection;
+    if union <= 0. {
+        return 0.;
+    }
+
+    intersection / union
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(x: i32, y: i32) -> (Region, OcrOutput) {
+        let region = Region {
+            id: 0,
+            bbox: BoundingBox { x: x, y: y, width: 100, height: 50 },
+            confidence: 1.,
+        };
+        (region, OcrOutput::default())
+    }
+
+    fn ids(delivered: &[(i32, Region, OcrOutput)]) -> Vec<(i32, usize)> {
+        delivered.iter().map(|(time_code, region, _)| (*time_code, region.id)).collect()
+    }
+
+    #[test]
+    fn confirmed_track_keeps_its_id() {
+        let mut tracker = RegionTracker::new(&TrackingConfig::default());
+
+        // the first sighting is held back until the track is confirmed
+        assert!(tracker.update(0, &mut [text(0, 0)]).is_empty());
+        assert_eq!(ids(&tracker.update(1, &mut [text(4, 2)])), vec![(0, 0), (1, 0)]);
+        // a miss does not end a confirmed track
+        assert!(tracker.update(2, &mut []).is_empty());
+        assert_eq!(ids(&tracker.update(3, &mut [text(2, 0)])), vec![(3, 0)]);
+    }
+
+    #[test]
+    fn flashing_region_is_dropped() {
+        let mut tracker = RegionTracker::new(&TrackingConfig::default());
+
+        assert!(tracker.update(0, &mut [text(0, 0), text(300, 300)]).is_empty());
+        assert_eq!(ids(&tracker.update(1, &mut [text(0, 0)])), vec![(0, 0), (1, 0)]);
+        // the region at a new place gets a new id
+        assert!(tracker.update(2, &mut [text(0, 0), text(300, 300)]).iter().all(|(_, region, _)| region.id == 0));
+    }
+
+    #[test]
+    fn ended_tracks_are_reported_once() {
+        let mut tracker = RegionTracker::new(&TrackingConfig::default());
+
+        tracker.update(0, &mut [text(0, 0), text(300, 300)]);
+        tracker.update(1, &mut [text(0, 0)]);
+        // the unconfirmed track ends with its first miss
+        assert_eq!(tracker.ended(), vec![1]);
+        assert!(tracker.ended().is_empty());
+
+        // the confirmed track ends after more than `max_misses` misses
+        for time_code in 2..2 + TrackingConfig::default().max_misses as i32 {
+            tracker.update(time_code, &mut []);
+            assert!(tracker.ended().is_empty());
+        }
+        tracker.update(10, &mut []);
+        assert_eq!(tracker.ended(), vec![0]);
+    }
+
+    #[test]
+    fn unconfirmed_track_is_delivered_at_the_end() {
+        let mut tracker = RegionTracker::new(&TrackingConfig::default());
+
+        assert!(tracker.update(0, &mut [text(0, 0)]).is_empty());
+        assert_eq!(ids(&tracker.finish()), vec![(0, 0)]);
+        assert!(tracker.finish().is_empty());
+    }
+
+    #[test]
+    fn track_follows_moving_region() {
+        let mut tracker = RegionTracker::new(&TrackingConfig::default());
+        tracker.update(0, &mut [text(0, 0)]);
+
+        // every step overlaps the previous box, but the last one not the first
+        for (time_code, x) in [(1, 40), (2, 80)] {
+            let mut texts = [text(x, 0)];
+            assert_eq!(ids(&tracker.update(time_code, &mut texts)).last(), Some(&(time_code, 0)));
+            // the text is reported with its detected box
+            assert_eq!(texts[0].0.bbox.x, x);
+        }
+    }
+
+    #[test]
+    fn disabled_tracking_gives_every_region_a_new_id() {
+        let config = TrackingConfig { enabled: false, ..TrackingConfig::default() };
+        let mut tracker = RegionTracker::new(&config);
+
+        assert_eq!(ids(&tracker.update(0, &mut [text(0, 0), text(300, 0)])), vec![(0, 0), (0, 1)]);
+        assert_eq!(ids(&tracker.update(1, &mut [text(0, 0)])), vec![(1, 2)]);
+        assert_eq!(tracker.ended(), vec![0, 1, 2]);
+    }
+}
@@ -2,21 +2,21 @@ use crate::prelude::*;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CJParser {
-    pub source: String,
+    pub source: Locator,
     pub knowledge_components: IndexSet<KnowledgeComponent>,
 }
 
 impl CJParser {
-    pub fn new(source: &str) -> Self {
+    pub fn new(source: &Locator) -> Self {
         Self {
-            source: source.into(),
+            source: source.clone(),
             knowledge_components: IndexSet::new()
         }
     }    
 }
 
 impl Parser for CJParser {
-    fn parse(&mut self, file: &str, time_code: i32) -> Result<(), Box<dyn Error>> {
+    fn parse(&mut self, file: &str, time_code: i32, region: Option<usize>) -> Result<(), ExtractionError> {
         let tokens: Vec<_> = Token::lexer(&file).collect();
         let mut token_iter = tokens.iter().enumerate();
         
@@ -24,7 +24,7 @@ impl Parser for CJParser {
         let mut knowledge_component: KnowledgeComponent;
         let mut plain_component: Component;
 
-        let time_stamp = format!("{}&t={}", self.source, time_code);
+        let time_stamp = self.source.time_stamp(time_code);
 
         // eprintln!("{:?}", tokens.clone());
         while let Some((idx, token)) = token_iter.next() {
@@ -80,35 +80,35 @@ impl Parser for CJParser {
                 Token::OpenBracket => {
                     // TODO: refine implementation
                     plain_component = parse_declarator(&Token::Array);
-                    knowledge_component = KnowledgeComponent::new(plain_component, &Token::Array, &time_stamp);
-                    knowledge_component_set.insert(knowledge_component);
+                    knowledge_component = KnowledgeComponent::new(plain_component, &Token::Array, &time_stamp).with_region(region);
+                    knowledge_component.merge_into(knowledge_component_set);
                     continue;
                 }
                 // function
                 Token::OpenParen => {
                     // TODO: refine implementation
                     plain_component = parse_declarator(&Token::Function);
-                    knowledge_component = KnowledgeComponent::new(plain_component, &Token::Function, &time_stamp);
-                    knowledge_component_set.insert(knowledge_component);
+                    knowledge_component = KnowledgeComponent::new(plain_component, &Token::Function, &time_stamp).with_region(region);
+                    knowledge_component.merge_into(knowledge_component_set);
                     continue;
                 }
                 // IDENTIFIER       // VARIABLE
                 // pointer
                 Token::Asterisk => {
-                    let peek = tokens.get(idx-1).unwrap_or(&Token::Error);
+                    let peek = idx.checked_sub(1).and_then(|prev| tokens.get(prev)).unwrap_or(&Token::Error);
                     match &*peek {
                         // check for multiplication
                         Token::Identifier(_) | Token::Number(_) => {
                             plain_component = parse_arithmetic(&Token::Multiplication);
-                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::Multiplication, &time_stamp);
-                            knowledge_component_set.insert(knowledge_component);
+                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::Multiplication, &time_stamp).with_region(region);
+                            knowledge_component.merge_into(knowledge_component_set);
                             continue;
                         }
                         // check for pointer operation
                         _ => {
                             plain_component = parse_declarator(&Token::Pointer);
-                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::Pointer, &time_stamp);
-                            knowledge_component_set.insert(knowledge_component);
+                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::Pointer, &time_stamp).with_region(region);
+                            knowledge_component.merge_into(knowledge_component_set);
                             continue;
                         }
                     }
@@ -134,19 +134,18 @@ impl Parser for CJParser {
                 }
                 // bitwise
                 Token::Ampersand | Token::BitwiseAnd | Token::Reference => {
-                    // TODO: potential 'attempt to subtract with overflow'
-                    let peek = tokens.get(idx-1).unwrap_or(&Token::Error);
+                    let peek = idx.checked_sub(1).and_then(|prev| tokens.get(prev)).unwrap_or(&Token::Error);
                     match &*peek {
                         Token::Identifier(_ident) | Token::Number(_ident) => {
                             plain_component = parse_bitwise(&Token::BitwiseAnd);
-                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::BitwiseAnd, &time_stamp);
-                            knowledge_component_set.insert(knowledge_component);
+                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::BitwiseAnd, &time_stamp).with_region(region);
+                            knowledge_component.merge_into(knowledge_component_set);
                             continue;
                         }
                         _ => {
                             plain_component = parse_bitwise(&Token::Reference);
-                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::Reference, &time_stamp);
-                            knowledge_component_set.insert(knowledge_component);
+                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::Reference, &time_stamp).with_region(region);
+                            knowledge_component.merge_into(knowledge_component_set);
                             continue;
                         }
                     }
@@ -156,7 +155,7 @@ impl Parser for CJParser {
                     plain_component = parse_bitwise(&token);
                 }
                 Token::LeftOperator => {
-                    let peek = tokens.get(idx-1).unwrap_or(&Token::Error);
+                    let peek = idx.checked_sub(1).and_then(|prev| tokens.get(prev)).unwrap_or(&Token::Error);
                     match &*peek {
                         // check whether lhs is stream operator
                         Token::Identifier(ident) => {
@@ -167,23 +166,23 @@ impl Parser for CJParser {
                                 }
                                 _ => {
                                     plain_component = parse_bitwise(&Token::LeftShift);
-                                    knowledge_component = KnowledgeComponent::new(plain_component, &Token::LeftShift, &time_stamp);
-                                    knowledge_component_set.insert(knowledge_component);
+                                    knowledge_component = KnowledgeComponent::new(plain_component, &Token::LeftShift, &time_stamp).with_region(region);
+                                    knowledge_component.merge_into(knowledge_component_set);
                                     continue;
                                 }
                             }
                         }
                         _ => {
                             plain_component = parse_bitwise(&Token::LeftShift);
-                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::LeftShift, &time_stamp);
-                            knowledge_component_set.insert(knowledge_component);
+                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::LeftShift, &time_stamp).with_region(region);
+                            knowledge_component.merge_into(knowledge_component_set);
                             continue;
                         }
                     }
                     plain_component = parse_bitwise(&token);
                 }
                 Token::RightOperator => {
-                    let peek = tokens.get(idx-1).unwrap_or(&Token::Error);
+                    let peek = idx.checked_sub(1).and_then(|prev| tokens.get(prev)).unwrap_or(&Token::Error);
                     match &*peek {
                         // check whether lhs is stream operator
                         Token::Identifier(ident) => {
@@ -194,16 +193,16 @@ impl Parser for CJParser {
                                 }
                                 _ => {
                                     plain_component = parse_bitwise(&Token::RightShift);
-                                    knowledge_component = KnowledgeComponent::new(plain_component, &Token::RightShift, &time_stamp);
-                                    knowledge_component_set.insert(knowledge_component);
+                                    knowledge_component = KnowledgeComponent::new(plain_component, &Token::RightShift, &time_stamp).with_region(region);
+                                    knowledge_component.merge_into(knowledge_component_set);
                                     continue;
                                 }
                             }
                         }
                         _ => {
                             plain_component = parse_bitwise(&Token::RightShift);
-                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::RightShift, &time_stamp);
-                            knowledge_component_set.insert(knowledge_component);
+                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::RightShift, &time_stamp).with_region(region);
+                            knowledge_component.merge_into(knowledge_component_set);
                             continue;
                         }
                     }
@@ -242,7 +241,7 @@ impl Parser for CJParser {
                 Token::Less | Token::Greater => {
                     // TODO: refine implementation
                     let peek_forward = tokens.get(idx+1).unwrap_or(&Token::Error);
-                    let peek_backword = tokens.get(idx-1).unwrap_or(&Token::Error);
+                    let peek_backword = idx.checked_sub(1).and_then(|prev| tokens.get(prev)).unwrap_or(&Token::Error);
 
                     match (peek_forward, peek_backword) {
                         (Token::Number(_), Token::Identifier(_)) => {
@@ -266,15 +265,15 @@ impl Parser for CJParser {
                         // check whether rhs is variable | number
                         Token::Identifier(_ident) | Token::Number(_ident) => {
                             plain_component = parse_increment(&Token::PrefixIncrement);
-                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::PrefixIncrement, &time_stamp);
-                            knowledge_component_set.insert(knowledge_component);
+                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::PrefixIncrement, &time_stamp).with_region(region);
+                            knowledge_component.merge_into(knowledge_component_set);
                             continue;
                         }
                         _ => {
                             // check whether lhs is variable | number
                             plain_component = parse_increment(&Token::PostfixIncrement);
-                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::PostfixIncrement, &time_stamp);
-                            knowledge_component_set.insert(knowledge_component);
+                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::PostfixIncrement, &time_stamp).with_region(region);
+                            knowledge_component.merge_into(knowledge_component_set);
                             continue;
                         }
                     }
@@ -285,15 +284,15 @@ impl Parser for CJParser {
                         // check whether rhs is variable | number
                         Token::Identifier(_ident) | Token::Number(_ident) => {
                             plain_component = parse_decrement(&Token::PrefixDecrement);
-                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::PrefixDecrement, &time_stamp);
-                            knowledge_component_set.insert(knowledge_component);
+                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::PrefixDecrement, &time_stamp).with_region(region);
+                            knowledge_component.merge_into(knowledge_component_set);
                             continue;
                         }
                         // check whether lhs is variable | number
                         _ => {
                             plain_component = parse_decrement(&Token::PostfixDecrement);
-                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::PostfixDecrement, &time_stamp);
-                            knowledge_component_set.insert(knowledge_component);
+                            knowledge_component = KnowledgeComponent::new(plain_component, &Token::PostfixDecrement, &time_stamp).with_region(region);
+                            knowledge_component.merge_into(knowledge_component_set);
                             continue;
                         }
                     }    
@@ -352,8 +351,8 @@ impl Parser for CJParser {
                     continue;
                 }
             }
-            knowledge_component = KnowledgeComponent::new(plain_component.clone(), token, &time_stamp);
-            knowledge_component_set.insert(knowledge_component.clone());
+            knowledge_component = KnowledgeComponent::new(plain_component.clone(), token, &time_stamp).with_region(region);
+            knowledge_component.clone().merge_into(knowledge_component_set);
         }
 
         Ok(())
@@ -362,6 +361,11 @@ impl Parser for CJParser {
     fn get_knowledge_components(&self) -> IndexSet<KnowledgeComponent> {
         self.knowledge_components.clone()
     }
+
+    #[cfg(feature = "video")]
+    fn tokens(&self, file: &str) -> Vec<String> {
+        Token::lexer(file).map(|token| token.to_string()).collect()
+    }
 }
 
 // Preprocessor -----------------------------------------------------
@@ -6,6 +6,9 @@ pub struct KnowledgeComponent {
     value: String,
     #[serde(rename = "timeStamp")]
     time_stamp: String,
+    /// detected regions the knowledge component was read from, e.g. both halves of a split editor
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    regions: BTreeSet<usize>,
     classification: Component,
 }
 
@@ -15,15 +18,37 @@ impl KnowledgeComponent {
             token: value.into(),
             value: value.into().to_lowercase(),
             time_stamp: time_stamp.into(),
+            regions: BTreeSet::new(),
             classification: classification,
         }
     }
 
+    /// Attributes the knowledge component to the detected region it was read from
+    pub fn with_region(mut self, region: Option<usize>) -> Self {
+        self.regions.extend(region);
+        self
+    }
+
+    /// Inserts the knowledge component into the set, the regions of an equal component are merged into the first occurrence
+    pub fn merge_into(self, set: &mut IndexSet<KnowledgeComponent>) {
+        let merged = match set.get(&self) {
+            Some(existing) if self.regions.is_subset(&existing.regions) => return,
+            Some(existing) => {
+                let mut merged = existing.clone();
+                merged.regions.extend(self.regions);
+                merged
+            }
+            None => self,
+        };
+        set.replace(merged);
+    }
+
     pub fn new_with_ident(classification: Component, value: &str, ident: &str, time_stamp: &str) -> Self {
         Self {
             token: value.into(),
             value: ident.into(),
             time_stamp: time_stamp.into(),
+            regions: BTreeSet::new(),
             classification: classification,
         }
     }
@@ -56,4 +81,65 @@ impl Component {
             node: Box::new(children),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Origin of the parsed source code, used to format the time stamp of a knowledge component
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Locator {
+    /// online video, time stamps are appended as url parameter
+    Url(String),
+    /// local video file, time stamps are appended as media fragment
+    File(String),
+    /// source code file, time codes are line numbers
+    Line(String),
+    /// slides or screenshots, time codes are indices into the image file names
+    Image(Vec<String>),
+}
+
+impl Locator {
+    pub fn time_stamp(&self, time_code: i32) -> String {
+        match self {
+            Locator::Url(url) => format!("{}&t={}", url, time_code),
+            Locator::File(path) => format!("{}#t={}", path, time_code),
+            Locator::Line(path) => format!("{}:{}", path, time_code),
+            Locator::Image(files) => files.get(time_code as usize).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(region: usize, time_stamp: &str) -> KnowledgeComponent {
+        KnowledgeComponent::new(Component::new("loop", None), "for", time_stamp).with_region(Some(region))
+    }
+
+    #[test]
+    fn merges_regions_of_equal_components() {
+        let mut set = IndexSet::new();
+        component(0, "t=1").merge_into(&mut set);
+        KnowledgeComponent::new(Component::new("array", None), "[]", "t=2").with_region(Some(0)).merge_into(&mut set);
+        component(1, "t=3").merge_into(&mut set);
+        component(0, "t=4").merge_into(&mut set);
+
+        assert_eq!(set.len(), 2);
+        let first = set.get_index(0).unwrap();
+        assert_eq!(first.regions.iter().copied().collect::<Vec<_>>(), vec![0, 1]);
+        // the first occurrence keeps its time stamp and position
+        assert_eq!(first.time_stamp, "t=1");
+        assert_eq!(set.get_index(1).unwrap().token, "[]");
+    }
+
+    #[test]
+    fn serializes_regions() {
+        let mut set = IndexSet::new();
+        component(2, "t=1").merge_into(&mut set);
+        component(0, "t=2").merge_into(&mut set);
+        KnowledgeComponent::new(Component::new("loop", None), "while", "t=3").merge_into(&mut set);
+
+        let serialized = serde_json::to_value(&set).unwrap();
+        assert_eq!(serialized[0]["regions"], serde_json::json!([0, 2]));
+        assert!(serialized[1].get("regions").is_none());
+    }
+}
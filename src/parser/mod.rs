@@ -1,6 +1,7 @@
 pub(crate) mod cjparser;
 pub(crate) mod pyparser;
 pub(crate) mod knowledge_component;
+pub(crate) mod router;
 
 use crate::prelude::*;
 
@@ -8,7 +9,8 @@ use crate::prelude::*;
 #[derive(Debug)]
 pub struct ProtoParser {
     pub parser: Option<Box<dyn Parser>>,
-    pub language: Option<Box<ProgrammingLanguage>>
+    pub language: Option<Box<ProgrammingLanguage>>,
+    pub classification: Option<Classification>,
 }
 
 impl ProtoParser {
@@ -16,10 +18,14 @@ impl ProtoParser {
         Self {
             parser: None,
             language: None,
+            classification: None,
         }
     }
 
-    pub fn parse_language(&mut self, source: &str, language: ProgrammingLanguage) {
+    pub fn parse_language(&mut self, source: &Locator, classification: Classification) {
+        let language = classification.language;
+        self.classification = Some(classification);
+
         match language {
             ProgrammingLanguage::C => {
                 let parser = CJParser::new(source);
@@ -44,20 +50,32 @@ impl ProtoParser {
         }
     }
 
-    pub fn parse(&mut self, file: &str, time_code: i32) -> Result<(), Box<dyn Error>> {      
+    /// Parses the text, the knowledge components are attributed to the region the text was read from
+    pub fn parse(&mut self, file: &str, time_code: i32, region: Option<usize>) -> Result<(), ExtractionError> {      
         if let Some(res) = self.parser.as_mut() {
-            res.parse(file, time_code).unwrap();
+            res.parse(file, time_code, region)?;
         }
 
         Ok(())
     }
 
+    /// Returns the tokens the lexer of the classified language produces for the text
+    #[cfg(feature = "video")]
+    pub fn tokens(&self, file: &str) -> Vec<String> {
+        self.parser.as_ref().map(|parser| parser.tokens(file)).unwrap_or_default()
+    }
+
+    /// Returns the extracted knowledge components, empty if the language was never classified
     pub fn get_knowledge_components(&self) -> IndexSet<KnowledgeComponent> {
-        self.parser.as_ref().unwrap().get_knowledge_components()
+        self.parser.as_ref()
+            .map(|parser| parser.get_knowledge_components())
+            .unwrap_or_default()
     }
 }
 
 pub trait Parser: std::fmt::Debug + Send {
-    fn parse(&mut self, file: &str, time_code: i32) -> Result<(), Box<dyn Error>>;
+    fn parse(&mut self, file: &str, time_code: i32, region: Option<usize>) -> Result<(), ExtractionError>;
     fn get_knowledge_components(&self) -> IndexSet<KnowledgeComponent>;
+    #[cfg(feature = "video")]
+    fn tokens(&self, file: &str) -> Vec<String>;
 }
\ No newline at end of file
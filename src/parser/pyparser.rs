@@ -2,21 +2,21 @@ use crate::prelude::*;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PyParser {
-    pub source: String,
+    pub source: Locator,
     pub knowledge_components: IndexSet<KnowledgeComponent>,
 }
 
 impl PyParser {
-    pub fn new(source: &str) -> Self {
+    pub fn new(source: &Locator) -> Self {
         Self {
-            source: source.into(),
+            source: source.clone(),
             knowledge_components: IndexSet::new(),
         }
     }
 }
 
 impl Parser for PyParser { 
-    fn parse(&mut self, file: &str, time_code: i32) -> Result<(), Box<dyn Error>> {
+    fn parse(&mut self, file: &str, time_code: i32, region: Option<usize>) -> Result<(), ExtractionError> {
         // let tokens: Vec<_> = PyToken::lexer(&file).collect();
         // let components = &mut self.knowledge_components;
         // let mut token_iter = tokens.iter().enumerate();
@@ -29,7 +29,7 @@ impl Parser for PyParser {
         let mut knowledge_component: KnowledgeComponent;
         let mut plain_component: Component;
 
-        let time_stamp = format!("{}&t={}", self.source, time_code);
+        let time_stamp = self.source.time_stamp(time_code);
 
         while let Some((idx, token)) = token_iter.next() {
             match token {
@@ -68,8 +68,8 @@ impl Parser for PyParser {
                     continue;
                 }
             }
-            knowledge_component = KnowledgeComponent::new(plain_component.clone(), token, &time_stamp);
-            knowledge_component_set.insert(knowledge_component.clone());
+            knowledge_component = KnowledgeComponent::new(plain_component.clone(), token, &time_stamp).with_region(region);
+            knowledge_component.clone().merge_into(knowledge_component_set);
         }
         Ok(())
     }
@@ -77,6 +77,11 @@ impl Parser for PyParser {
     fn get_knowledge_components(&self) -> IndexSet<KnowledgeComponent> {
         self.knowledge_components.clone()
     }
+
+    #[cfg(feature = "video")]
+    fn tokens(&self, file: &str) -> Vec<String> {
+        PyToken::lexer(file).map(|token| token.to_string()).collect()
+    }
 }
 
 
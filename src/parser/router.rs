@@ -0,0 +1,207 @@
+use crate::prelude::*;
+
+/// Parsers of the languages shown in a video, the texts of every region are parsed by the parser of the region's language
+#[cfg(any(feature = "video", test))]
+#[derive(Debug)]
+pub struct LanguageRouter {
+    locator: Locator,
+    /// parser of every assigned language, in the order the languages were assigned
+    pub parsers: Vec<ProtoParser>,
+    /// classification of every region
+    regions: HashMap<usize, Classification>,
+    /// first and last time code every region was parsed at
+    spans: HashMap<usize, (i32, i32)>,
+}
+
+/// Screen time and classification of a language shown in a video
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageShare {
+    pub language: ProgrammingLanguage,
+    /// fraction of the screen time of all parsed regions
+    pub share: f64,
+    /// confidence and origin of the classification of the region with the most screen time
+    pub confidence: f64,
+    pub source: ClassificationSource,
+    pub regions: Vec<usize>,
+}
+
+#[cfg(any(feature = "video", test))]
+impl LanguageRouter {
+    pub fn new(locator: &Locator) -> Self {
+        Self {
+            locator: locator.clone(),
+            parsers: Vec::new(),
+            regions: HashMap::new(),
+            spans: HashMap::new(),
+        }
+    }
+
+    /// Routes the texts of the region to the parser of its language, the parser is created with the first region of a language
+    pub fn assign(&mut self, region: usize, classification: Classification) {
+        if self.parser(classification.language).is_none() {
+            let mut parser = ProtoParser::new();
+            parser.parse_language(&self.locator, classification.clone());
+            self.parsers.push(parser);
+        }
+        self.regions.insert(region, classification);
+    }
+
+    pub fn language(&self, region: usize) -> Option<ProgrammingLanguage> {
+        self.regions.get(&region).map(|classification| classification.language)
+    }
+
+    /// Parses the text of a region with the parser of its language, texts of unassigned regions are skipped
+    pub fn parse(&mut self, text: &str, time_code: i32, region: usize) -> Result<(), ExtractionError> {
+        let language = match self.language(region) {
+            Some(language) => language,
+            None => return Ok(()),
+        };
+
+        let span = self.spans.entry(region).or_insert((time_code, time_code));
+        span.0 = span.0.min(time_code);
+        span.1 = span.1.max(time_code);
+
+        match self.parsers.iter_mut().find(|parser| parser.language.as_deref() == Some(&language)) {
+            Some(parser) => parser.parse(text, time_code, Some(region)),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the classification of the language with the most screen time
+    pub fn primary(&self) -> Option<Classification> {
+        let share = self.shares().into_iter().next()?;
+        self.parser(share.language)?.classification.clone()
+    }
+
+    /// Returns the tokens the lexer of the region's language produces for the text, none if the region is unassigned
+    #[cfg(feature = "video")]
+    pub fn tokens(&self, region: usize, text: &str) -> Vec<String> {
+        self.language(region)
+            .and_then(|language| self.parser(language))
+            .map(|parser| parser.tokens(text))
+            .unwrap_or_default()
+    }
+
+    /// Returns the knowledge components of every language, keyed by the lowercase language
+    pub fn knowledge_components(&self) -> IndexMap<String, IndexSet<KnowledgeComponent>> {
+        self.shares().iter()
+            .filter_map(|share| self.parser(share.language))
+            .filter_map(|parser| {
+                let language = parser.language.as_ref()?.to_string().to_lowercase();
+                Some((language, parser.get_knowledge_components()))
+            })
+            .collect()
+    }
+
+    /// Returns the share of the screen time of every language, the largest first.
+    /// A region is on screen from the first to the last time it was read, at least for one time code.
+    pub fn shares(&self) -> Vec<LanguageShare> {
+        let screen_time = |region: &usize| self.spans.get(region).map(|(first, last)| (last - first + 1) as f64).unwrap_or(0.);
+        let total: f64 = self.regions.keys().map(screen_time).sum();
+
+        let mut shares: Vec<LanguageShare> = Vec::new();
+        for parser in self.parsers.iter() {
+            let language = match parser.language.as_deref() {
+                Some(language) => *language,
+                None => continue,
+            };
+            let mut regions: Vec<usize> = self.regions.iter()
+                .filter(|(_, classification)| classification.language == language)
+                .map(|(region, _)| *region)
+                .collect();
+            regions.sort();
+
+            let time: f64 = regions.iter().map(screen_time).sum();
+            let longest = regions.iter()
+                .max_by(|a, b| screen_time(a).partial_cmp(&screen_time(b)).unwrap_or(std::cmp::Ordering::Equal))
+                .and_then(|region| self.regions.get(region));
+
+            if let Some(classification) = longest {
+                shares.push(LanguageShare {
+                    language,
+                    share: if total > 0. { time / total } else { 0. },
+                    confidence: classification.confidence,
+                    source: classification.source,
+                    regions,
+                });
+            }
+        }
+        shares.sort_by(|a, b| b.share.partial_cmp(&a.share).unwrap_or(std::cmp::Ordering::Equal));
+
+        shares
+    }
+
+    fn parser(&self, language: ProgrammingLanguage) -> Option<&ProtoParser> {
+        self.parsers.iter().find(|parser| parser.language.as_deref() == Some(&language))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ProgrammingLanguage::*;
+
+    const C_PROGRAM: &str = "int main() {\n    return 0;\n}";
+    const PYTHON_PROGRAM: &str = "def main():\n    return 0";
+
+    /// Region 0 shows C from time code 0 to 2, region 1 Python from 1 to 10 and region 2 C at 5
+    fn router() -> LanguageRouter {
+        let mut router = LanguageRouter::new(&Locator::File("v.mp4".into()));
+        router.assign(0, Classification::certain(C, ClassificationSource::Code));
+        router.assign(1, Classification::certain(Python, ClassificationSource::Metadata));
+        router.assign(2, Classification::certain(C, ClassificationSource::Code));
+
+        for (text, time_code, region) in [(C_PROGRAM, 0, 0), (C_PROGRAM, 2, 0), (PYTHON_PROGRAM, 1, 1), (PYTHON_PROGRAM, 10, 1), (C_PROGRAM, 5, 2)] {
+            router.parse(text, time_code, region).unwrap();
+        }
+        router
+    }
+
+    #[test]
+    fn shares_are_ranked_by_screen_time() {
+        let shares = router().shares();
+
+        assert_eq!(shares.len(), 2);
+        assert_eq!(shares[0].language, Python);
+        assert!((shares[0].share - 10. / 14.).abs() < 1e-9);
+        assert_eq!(shares[0].source, ClassificationSource::Metadata);
+        assert_eq!(shares[1].language, C);
+        assert!((shares[1].share - 4. / 14.).abs() < 1e-9);
+        assert_eq!(shares[1].regions, vec![0, 2]);
+    }
+
+    #[test]
+    fn texts_of_unassigned_regions_are_skipped() {
+        let mut router = router();
+        router.parse(C_PROGRAM, 100, 3).unwrap();
+
+        assert_eq!(router.language(3), None);
+        assert!((router.shares()[0].share - 10. / 14.).abs() < 1e-9);
+        assert_eq!(router.primary().unwrap().language, Python);
+    }
+
+    #[test]
+    fn knowledge_components_are_keyed_by_language() {
+        let components = router().knowledge_components();
+
+        assert_eq!(components.keys().collect::<Vec<_>>(), vec!["python", "c"]);
+        assert!(components.values().all(|components| !components.is_empty()));
+        // both regions showing C are recorded with the components of the C parser
+        let components = serde_json::to_value(&components["c"]).unwrap();
+        let regions: BTreeSet<u64> = components.as_array().unwrap().iter()
+            .flat_map(|component| component["regions"].as_array().unwrap().iter().map(|region| region.as_u64().unwrap()))
+            .collect();
+        assert_eq!(regions, BTreeSet::from([0, 2]));
+    }
+
+    #[cfg(feature = "video")]
+    #[test]
+    fn tokens_are_lexed_by_the_language_of_the_region() {
+        let router = router();
+
+        assert_eq!(router.tokens(1, "def"), vec!["FunctionDefinition"]);
+        assert_ne!(router.tokens(0, "def"), router.tokens(1, "def"));
+        assert!(router.tokens(3, "def").is_empty());
+    }
+}
@@ -0,0 +1 @@
+pub(crate) mod reconstruction;
\ No newline at end of file
@@ -0,0 +1,383 @@
+use crate::prelude::*;
+
+/// Lines at least this similar are taken as the same line, changed by an edit or misread by the OCR
+#[cfg(any(feature = "video", test))]
+const SIMILARITY_THRESHOLD: f64 = 0.75;
+
+/// Reconstructs the source files edited in a video from the OCR snapshots of its regions
+#[cfg(feature = "video")]
+#[derive(Debug, Default)]
+pub struct SourceReconstruction {
+    pub files: BTreeMap<usize, SourceFile>,
+}
+
+#[cfg(feature = "video")]
+impl SourceReconstruction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aligns the snapshot of a region with the file reconstructed so far and records the differences as edits
+    pub fn update(&mut self, region: usize, snapshot: &str, time_code: i32) {
+        self.files.entry(region)
+            .or_insert_with(|| SourceFile::new(region))
+            .update(snapshot, time_code);
+    }
+
+    pub fn into_files(self) -> Vec<SourceFile> {
+        self.files.into_values().collect()
+    }
+}
+
+/// Current state of a source file along with the timed edits that led to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceFile {
+    pub region: usize,
+    /// language of the region, if it was classified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<ProgrammingLanguage>,
+    pub lines: Vec<String>,
+    /// edits in the order they were applied, replaying them on an empty file yields the current state
+    pub edits: Vec<Edit>,
+    /// range of the lines shown by the last view
+    #[serde(skip)]
+    #[cfg(any(feature = "video", test))]
+    shown: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Edit {
+    pub time_code: i32,
+    /// first affected line, starting at 1
+    pub line: usize,
+    pub removed: Vec<String>,
+    pub inserted: Vec<String>,
+}
+
+impl SourceFile {
+    /// Returns the reconstructed program
+    pub fn program(&self) -> String {
+        self.lines.join("\n")
+    }
+}
+
+#[cfg(any(feature = "video", test))]
+impl SourceFile {
+    pub fn new(region: usize) -> Self {
+        Self {
+            region: region,
+            language: None,
+            lines: Vec::new(),
+            edits: Vec::new(),
+            shown: None,
+        }
+    }
+
+    pub fn update(&mut self, snapshot: &str, time_code: i32) {
+        let snapshot = view_lines(snapshot);
+        if snapshot.is_empty() {
+            return;
+        }
+
+        // a view sharing no line with the file replaces the lines shown last, e.g. a line typed over or rewritten,
+        // the first view of a file is its start
+        let (start, end) = self.locate(&snapshot)
+            .or(self.shown)
+            .unwrap_or((self.lines.len(), self.lines.len()));
+        let hunks = diff(&self.lines[start..end], &snapshot);
+
+        // apply from bottom to top, so the line numbers of the remaining hunks stay valid
+        for hunk in hunks.into_iter().rev() {
+            self.apply(time_code, start + hunk.start, hunk.removed, hunk.inserted);
+        }
+        // the range shows the view now
+        self.shown = Some((start, start + snapshot.len()));
+    }
+
+    /// Returns the range of the file shown by the snapshot, the view may be scrolled or only show a part of the file
+    fn locate(&self, snapshot: &[String]) -> Option<(usize, usize)> {
+        // vote for the offset of the view by the lines it shares with the file, a line occurring often like a lone brace
+        // says little about the position and shares its vote among its occurrences
+        let mut votes: BTreeMap<isize, f64> = BTreeMap::new();
+        for (snapshot_idx, line) in snapshot.iter().enumerate() {
+            let occurrences: Vec<(usize, f64)> = self.lines.iter()
+                .map(|file_line| similarity(file_line, line))
+                .enumerate()
+                .filter(|(_, similarity)| *similarity >= SIMILARITY_THRESHOLD)
+                .collect();
+            for (file_idx, similarity) in occurrences.iter() {
+                *votes.entry(*file_idx as isize - snapshot_idx as isize).or_default() += similarity / occurrences.len() as f64;
+            }
+        }
+
+        // equal votes prefer the offset closest to the lines shown last
+        let last = self.shown.map(|(start, _)| start as isize).unwrap_or(0);
+        let offset = votes.into_iter()
+            .max_by(|(a, a_votes), (b, b_votes)| {
+                a_votes.partial_cmp(b_votes)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| (b - last).abs().cmp(&(a - last).abs()))
+            })?
+            .0;
+
+        // inserted or removed lines within the view shift the lines below, so the range is taken from the matches
+        let len = self.lines.len() as isize;
+        let view = snapshot.len() as isize;
+        let window_start = (offset - view).clamp(0, len) as usize;
+        let window_end = (offset + 2 * view).clamp(0, len) as usize;
+        let matches = lcs(&self.lines[window_start..window_end], snapshot);
+
+        let (first_file, first_snapshot) = matches.first()?;
+        let (last_file, last_snapshot) = matches.last()?;
+        let start = (window_start + first_file).saturating_sub(*first_snapshot);
+        let end = (window_start + last_file + snapshot.len() - last_snapshot).min(self.lines.len());
+
+        Some((start, end))
+    }
+
+    fn apply(&mut self, time_code: i32, start: usize, removed: usize, inserted: Vec<String>) {
+        let removed: Vec<_> = self.lines.splice(start..start + removed, inserted.iter().cloned()).collect();
+
+        self.edits.push(Edit {
+            time_code: time_code,
+            line: start + 1,
+            removed: removed,
+            inserted: inserted,
+        });
+    }
+}
+
+/// Lines of the view without trailing whitespace and without the blank lines at its edges
+#[cfg(any(feature = "video", test))]
+fn view_lines(snapshot: &str) -> Vec<String> {
+    let lines: Vec<String> = snapshot.lines().map(|line| line.trim_end().to_string()).collect();
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+
+    match (first, last) {
+        (Some(first), Some(last)) => lines[first..=last].to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+/// Similarity of two lines between 0 and 1, one minus their edit distance relative to the longer line
+#[cfg(any(feature = "video", test))]
+fn similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.;
+    }
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len()) as f64;
+
+    // the distance is at least the difference of the lengths, which rules out most pairs without computing it
+    if 1. - (a.len() as f64 - b.len() as f64).abs() / longest < SIMILARITY_THRESHOLD {
+        return 0.;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == b_char { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    1. - previous[b.len()] as f64 / longest
+}
+
+/// Replaces `removed` lines at `start` of the old lines with the `inserted` lines
+#[cfg(any(feature = "video", test))]
+struct Hunk {
+    start: usize,
+    removed: usize,
+    inserted: Vec<String>,
+}
+
+#[cfg(any(feature = "video", test))]
+fn diff(old: &[String], new: &[String]) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+    let (mut old_idx, mut new_idx) = (0, 0);
+
+    // the lines between two equal lines form a hunk, similar but changed lines are part of it,
+    // a sentinel match closes the last one
+    for (old_match, new_match) in lcs(old, new).into_iter().chain(std::iter::once((old.len(), new.len()))) {
+        if old_match < old.len() && old[old_match] != new[new_match] {
+            continue;
+        }
+        if old_match > old_idx || new_match > new_idx {
+            hunks.push(Hunk {
+                start: old_idx,
+                removed: old_match - old_idx,
+                inserted: new[new_idx..new_match].to_vec(),
+            });
+        }
+        old_idx = old_match + 1;
+        new_idx = new_match + 1;
+    }
+
+    hunks
+}
+
+/// Weight of a pair of lines in the common subsequence, equal lines outweigh any two similar ones,
+/// so similar lines like `int a = 1;` and `int b = 1;` do not shift the alignment of the equal lines
+#[cfg(any(feature = "video", test))]
+fn match_weight(a: &str, b: &str) -> f64 {
+    let similarity = similarity(a, b);
+    if similarity == 1. {
+        1.
+    } else if similarity >= SIMILARITY_THRESHOLD {
+        similarity / 2.
+    } else {
+        0.
+    }
+}
+
+/// Returns the index pairs of the heaviest common subsequence of similar lines
+#[cfg(any(feature = "video", test))]
+fn lcs(old: &[String], new: &[String]) -> Vec<(usize, usize)> {
+    let weights: Vec<Vec<f64>> = old.iter().map(|a| new.iter().map(|b| match_weight(a, b)).collect()).collect();
+    let mut lengths = vec![vec![0_f64; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            let skip = lengths[i + 1][j].max(lengths[i][j + 1]);
+            lengths[i][j] = if weights[i][j] > 0. { skip.max(lengths[i + 1][j + 1] + weights[i][j]) } else { skip };
+        }
+    }
+
+    let mut matches = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if weights[i][j] > 0. && lengths[i][j] == lengths[i + 1][j + 1] + weights[i][j] {
+            matches.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(views: &[&str]) -> SourceFile {
+        let mut file = SourceFile::new(0);
+        for (time_code, view) in views.iter().enumerate() {
+            file.update(view, time_code as i32);
+        }
+        file
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    fn similar(a: &str, b: &str) -> bool {
+        similarity(a, b) >= SIMILARITY_THRESHOLD
+    }
+
+    fn file(text: &str) -> SourceFile {
+        let mut file = SourceFile::new(0);
+        file.lines = lines(text);
+        file
+    }
+
+    #[test]
+    fn edited_line_replaces_previous_version() {
+        let file = replay(&["int x = 1;", "int x = 2;"]);
+
+        assert_eq!(file.lines, vec!["int x = 2;"]);
+        assert_eq!(file.edits.len(), 2);
+        assert_eq!(file.edits[1].removed, vec!["int x = 1;"]);
+        assert_eq!(file.edits[1].inserted, vec!["int x = 2;"]);
+    }
+
+    #[test]
+    fn typed_line_grows() {
+        let file = replay(&["import o", "import os", "import os\nimport sys"]);
+        assert_eq!(file.lines, vec!["import os", "import sys"]);
+
+        let file = replay(&["int main() {", "int main() {\n    return 0;", "int main() {\n    return 0;\n}"]);
+        assert_eq!(file.program(), "int main() {\n    return 0;\n}");
+    }
+
+    #[test]
+    fn misread_view_does_not_duplicate() {
+        let file = replay(&[
+            "int a = 1;\nint b = 2;\nreturn a + b;",
+            "int a = l;\nint b = 2;\nreturn a + b;",
+            "int a = 1;\nint b = 2;\nreturn a + b;",
+        ]);
+
+        assert_eq!(file.program(), "int a = 1;\nint b = 2;\nreturn a + b;");
+    }
+
+    #[test]
+    fn short_lines_are_located() {
+        let file = replay(&["x", "y", "x\ny"]);
+        assert_eq!(file.lines, vec!["x", "y"]);
+
+        let file = replay(&["if (a) {\n}", "if (a) {\n    b();\n}"]);
+        assert_eq!(file.program(), "if (a) {\n    b();\n}");
+    }
+
+    #[test]
+    fn scrolled_view_edits_its_part_of_the_file() {
+        let program: Vec<String> = (1..=10).map(|idx| format!("int value{} = {};", idx, idx)).collect();
+        let mut view = program[4..8].to_vec();
+        view[1] = "int value6 = 60;".into();
+
+        let file = replay(&[&program.join("\n"), &view.join("\n")]);
+
+        let mut expected = program.clone();
+        expected[5] = "int value6 = 60;".into();
+        assert_eq!(file.lines, expected);
+        assert_eq!(file.edits[1].line, 6);
+    }
+
+    #[test]
+    fn locates_scrolled_view() {
+        let file = file("a = 1\nb = 2\nc = 3\nd = 4\ne = 5");
+
+        assert_eq!(file.locate(&lines("c = 3\nd = 4")), Some((2, 4)));
+        // an inserted line within the view keeps the range of the file
+        assert_eq!(file.locate(&lines("b = 2\nx = 9\nc = 3")), Some((1, 3)));
+        assert_eq!(file.locate(&lines("something else")), None);
+        assert_eq!(SourceFile::new(0).locate(&lines("a = 1")), None);
+    }
+
+    #[test]
+    fn diff_keeps_equal_lines() {
+        let hunks = diff(&lines("a = 1\nb = 2\nc = 3"), &lines("a = 1\nb = 20\nnew line\nc = 3"));
+
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0].start, hunks[0].removed), (1, 1));
+        assert_eq!(hunks[0].inserted, lines("b = 20\nnew line"));
+
+        let hunks = diff(&lines("a = 1\nb = 2"), &lines("b = 2"));
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0].start, hunks[0].removed, hunks[0].inserted.len()), (0, 1, 0));
+
+        assert!(diff(&lines("a = 1"), &lines("a = 1")).is_empty());
+    }
+
+    #[test]
+    fn similarity_by_edit_distance() {
+        assert_eq!(similarity("int x = 1;", "int x = 1;"), 1.);
+        assert!(similar("int x = 1;", "int x = 2;"));
+        assert!(similar("import o", "import os"));
+        assert!(!similar("x", "y"));
+        assert!(!similar("}", "};"));
+        assert!(!similar("int x = 1;", "return 0;"));
+    }
+}
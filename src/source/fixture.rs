@@ -0,0 +1,111 @@
+use crate::prelude::*;
+
+/// Reads playlists and videos from a local json listing instead of querying yt-dlp,
+/// media paths of the entries are relative to the listing
+#[derive(Debug)]
+pub struct Fixture {
+    pub path: String,
+}
+
+impl Fixture {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.into(),
+        }
+    }
+
+    fn read_listing(&self) -> Result<Playlist, ExtractionError> {
+        let listing = read_to_string(&self.path)?;
+        let playlist = serde_json::from_str(&listing)
+            .map_err(|err| ExtractionError::Download(format!("invalid fixture listing {}: {}", self.path, err)))?;
+
+        Ok(playlist)
+    }
+}
+
+impl PlaylistSource for Fixture {
+    fn resolve_playlist(&self, _playlist: &str) -> Result<Playlist, ExtractionError> {
+        self.read_listing()
+    }
+}
+
+impl VideoSource for Fixture {
+    fn resolve(&self, video: &str) -> Result<VideoMetadata, ExtractionError> {
+        self.read_listing()?
+            .entries
+            .into_iter()
+            .find(|entry| entry.url == video)
+            .ok_or_else(|| ExtractionError::Download(format!("fixture {} has no video {}", self.path, video)))
+    }
+
+    fn fetch(&self, video: &VideoMetadata, _directory: &Path) -> Result<PathBuf, ExtractionError> {
+        let media = video.path.as_ref()
+            .ok_or_else(|| ExtractionError::Download(format!("fixture {} has no media for {}", self.path, video.url)))?;
+        let path = Path::new(&self.path).parent().unwrap_or_else(|| Path::new(".")).join(media);
+
+        if !path.is_file() {
+            return Err(ExtractionError::Download(format!("fixture media {} does not exist", path.display())));
+        }
+
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_fixture(name: &str) -> PathBuf {
+        let dir = test_dir("fixture", name);
+        let listing = r#"{"title": "Course", "url": "playlist", "entries": [
+            {"title": "First", "url": "first", "path": "media/first.mp4"},
+            {"title": "Second", "url": "second", "path": "media/second.mp4"},
+            {"title": "Third", "url": "third"}
+        ]}"#;
+        write_files(&dir, &[("media/first.mp4", ""), ("listing.json", listing)]);
+        dir
+    }
+
+    #[test]
+    fn resolves_entries_of_listing() {
+        let dir = create_fixture("resolve");
+        let fixture = Fixture::new(&dir.join("listing.json").display().to_string());
+
+        let playlist = fixture.resolve_playlist("ignored").unwrap();
+        assert_eq!(playlist.title, "Course");
+        assert_eq!(playlist.entries.len(), 3);
+
+        assert_eq!(fixture.resolve("second").unwrap().title, "Second");
+        assert!(fixture.resolve("fourth").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn fetches_media_relative_to_listing() {
+        let dir = create_fixture("fetch");
+        let fixture = Fixture::new(&dir.join("listing.json").display().to_string());
+
+        let first = fixture.resolve("first").unwrap();
+        assert_eq!(fixture.fetch(&first, Path::new("video")).unwrap(), dir.join("media/first.mp4"));
+        // the media of the second video is missing, the third has none
+        assert!(fixture.fetch(&fixture.resolve("second").unwrap(), Path::new("video")).is_err());
+        assert!(fixture.fetch(&fixture.resolve("third").unwrap(), Path::new("video")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_listing_is_a_download_error() {
+        let dir = create_fixture("invalid");
+        write(dir.join("listing.json"), "{").unwrap();
+        let fixture = Fixture::new(&dir.join("listing.json").display().to_string());
+
+        match fixture.resolve_playlist("ignored") {
+            Err(ExtractionError::Download(msg)) => assert!(msg.contains("invalid fixture listing")),
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
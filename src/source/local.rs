@@ -0,0 +1,88 @@
+use crate::prelude::*;
+
+/// Video file that is already stored on disk, the metadata is read from a yt-dlp info json
+/// next to it (`video.info.json` for `video.mp4`), if there is one
+#[derive(Debug, Default)]
+pub struct LocalFile;
+
+impl LocalFile {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl VideoSource for LocalFile {
+    fn resolve(&self, video: &str) -> Result<VideoMetadata, ExtractionError> {
+        let path = std::fs::canonicalize(video)
+            .map_err(|err| ExtractionError::Download(format!("could not open video file {}: {}", video, err)))?
+            .display()
+            .to_string();
+
+        let metadata = VideoMetadata::new(&get_file_title(&path), &path, Some(path.clone()));
+        let sidecar = Path::new(&path).with_extension("info.json");
+
+        if !sidecar.is_file() {
+            return Ok(metadata);
+        }
+        let info: serde_json::Value = serde_json::from_str(&read_to_string(&sidecar)?)
+            .map_err(|err| ExtractionError::Download(format!("invalid info json {}: {}", sidecar.display(), err)))?;
+
+        Ok(metadata.with_info(&info))
+    }
+
+    fn fetch(&self, video: &VideoMetadata, _directory: &Path) -> Result<PathBuf, ExtractionError> {
+        let path = PathBuf::from(video.path.as_ref().unwrap_or(&video.url));
+
+        if !path.is_file() {
+            return Err(ExtractionError::Download(format!("video file {} does not exist", path.display())));
+        }
+
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_video(name: &str, sidecar: Option<&str>) -> PathBuf {
+        let dir = test_dir("local", name);
+        write_files(&dir, &[("lecture.mp4", "")]);
+        if let Some(sidecar) = sidecar {
+            write_files(&dir, &[("lecture.info.json", sidecar)]);
+        }
+        std::fs::canonicalize(dir.join("lecture.mp4")).unwrap()
+    }
+
+    #[test]
+    fn resolves_title_of_file_without_sidecar() {
+        let video = create_video("plain", None);
+        let metadata = LocalFile::new().resolve(&video.display().to_string()).unwrap();
+
+        assert_eq!(metadata.title, "lecture");
+        assert_eq!(metadata.path.as_deref(), Some(video.display().to_string().as_str()));
+        assert_eq!(LocalFile::new().fetch(&metadata, Path::new("video")).unwrap(), video);
+
+        std::fs::remove_dir_all(video.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn resolves_metadata_of_sidecar() {
+        let video = create_video("sidecar", Some(r#"{"title": "TCP/IP in C", "tags": ["c", "networking"]}"#));
+        let metadata = LocalFile::new().resolve(&video.display().to_string()).unwrap();
+
+        assert_eq!(metadata.title, "TCP/IP in C");
+        assert_eq!(metadata.tags, vec!["c", "networking"]);
+
+        std::fs::remove_dir_all(video.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_sidecar_and_missing_file() {
+        let video = create_video("invalid", Some("{"));
+        assert!(LocalFile::new().resolve(&video.display().to_string()).is_err());
+        assert!(LocalFile::new().resolve(&video.with_extension("mkv").display().to_string()).is_err());
+
+        std::fs::remove_dir_all(video.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,90 @@
+pub(crate) mod fixture;
+pub(crate) mod local;
+pub(crate) mod ytdlp;
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Playlist {
+    pub title: String,
+    pub url: String,
+    pub entries: Vec<VideoMetadata>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoMetadata {
+    pub title: String,
+    pub url: String,
+    /// local media file, if the video does not need to be downloaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl VideoMetadata {
+    pub fn new(title: &str, url: &str, path: Option<String>) -> Self {
+        Self {
+            title: title.into(),
+            url: url.into(),
+            path: path,
+            description: String::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Takes title, description and tags from the info json of yt-dlp, missing values are kept
+    pub fn with_info(mut self, info: &serde_json::Value) -> Self {
+        if let Some(title) = info["title"].as_str().filter(|title| !title.is_empty()) {
+            self.title = title.into();
+        }
+        if let Some(description) = info["description"].as_str() {
+            self.description = description.into();
+        }
+        if let Some(tags) = info["tags"].as_array() {
+            self.tags = tags.iter().filter_map(|tag| tag.as_str()).map(String::from).collect();
+        }
+
+        self
+    }
+}
+
+/// Expands a playlist into its video entries
+pub trait PlaylistSource {
+    fn resolve_playlist(&self, playlist: &str) -> Result<Playlist, ExtractionError>;
+}
+
+/// Resolves the metadata of a video and fetches its media to a local path
+pub trait VideoSource: std::fmt::Debug + Send + Sync {
+    fn resolve(&self, video: &str) -> Result<VideoMetadata, ExtractionError>;
+    #[cfg_attr(not(feature = "video"), allow(dead_code))]
+    fn fetch(&self, video: &VideoMetadata, directory: &Path) -> Result<PathBuf, ExtractionError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info_overrides_title_description_and_tags() {
+        let info = serde_json::json!({"title": "Pointers in C", "description": "learn c", "tags": ["c", 1, "programming"]});
+        let metadata = VideoMetadata::new("pointers", "url", None).with_info(&info);
+
+        assert_eq!(metadata.title, "Pointers in C");
+        assert_eq!(metadata.description, "learn c");
+        assert_eq!(metadata.tags, vec!["c", "programming"]);
+    }
+
+    #[test]
+    fn info_keeps_missing_values() {
+        let info = serde_json::json!({"title": ""});
+        let metadata = VideoMetadata::new("pointers", "url", Some("pointers.mp4".into())).with_info(&info);
+
+        assert_eq!(metadata.title, "pointers");
+        assert_eq!(metadata.path.as_deref(), Some("pointers.mp4"));
+        assert!(metadata.description.is_empty());
+        assert!(metadata.tags.is_empty());
+    }
+}
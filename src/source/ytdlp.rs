@@ -0,0 +1,97 @@
+use crate::prelude::*;
+
+/// Prefers the 1080p webm video stream and falls back to the best available video
+pub const DEFAULT_FORMAT: &str = "248/bestvideo[height<=1080]/best";
+
+#[derive(Debug)]
+pub struct YtDlp {
+    #[cfg_attr(not(feature = "video"), allow(dead_code))]
+    pub format: String,
+}
+
+impl YtDlp {
+    pub fn new() -> Self {
+        Self::with_format(DEFAULT_FORMAT)
+    }
+
+    pub fn with_format(format: &str) -> Self {
+        Self {
+            format: format.into(),
+        }
+    }
+}
+
+impl Default for YtDlp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlaylistSource for YtDlp {
+    fn resolve_playlist(&self, playlist: &str) -> Result<Playlist, ExtractionError> {
+        let stdout = run_yt_dlp(&["--flat-playlist", "-J", playlist])?;
+        let json: serde_json::Value = serde_json::from_str(&stdout)
+            .map_err(|err| ExtractionError::Download(format!("invalid yt-dlp output for {}: {}", playlist, err)))?;
+        let entries = json["entries"].as_array()
+            .ok_or_else(|| ExtractionError::Download(format!("{} is not a playlist", playlist)))?
+            .iter()
+            .filter_map(parse_entry)
+            .collect();
+
+        Ok(Playlist {
+            title: json["title"].as_str().unwrap_or(playlist).into(),
+            url: playlist.into(),
+            entries: entries,
+        })
+    }
+}
+
+impl VideoSource for YtDlp {
+    fn resolve(&self, video: &str) -> Result<VideoMetadata, ExtractionError> {
+        let stdout = run_yt_dlp(&["--dump-json", "--no-playlist", video])?;
+        let info: serde_json::Value = serde_json::from_str(&stdout)
+            .map_err(|err| ExtractionError::Download(format!("invalid yt-dlp output for {}: {}", video, err)))?;
+        let metadata = VideoMetadata::new("", video, None).with_info(&info);
+
+        if metadata.title.is_empty() {
+            return Err(ExtractionError::Download(format!("yt-dlp could not get video title of {}", video)));
+        }
+
+        Ok(metadata)
+    }
+
+    fn fetch(&self, video: &VideoMetadata, directory: &Path) -> Result<PathBuf, ExtractionError> {
+        let file = format!("{}.mp4", file_name(&video.title));
+        let path = directory.join(&file);
+
+        run_yt_dlp(&["-f", &self.format, "-P", &directory.display().to_string(), "-o", &file, &video.url])?;
+
+        if !path.is_file() {
+            return Err(ExtractionError::Download(format!("yt-dlp did not download {} to {}", video.url, path.display())));
+        }
+
+        Ok(path)
+    }
+}
+
+fn run_yt_dlp(args: &[&str]) -> Result<String, ExtractionError> {
+    let command_output = Command::new("yt-dlp")
+        .args(args)
+        .output()
+        .map_err(|err| ExtractionError::Download(format!("yt-dlp could not be executed: {}", err)))?;
+
+    if !command_output.status.success() {
+        return Err(ExtractionError::Download(format!("yt-dlp {} failed: {}", args.join(" "), String::from_utf8_lossy(&command_output.stderr).trim())));
+    }
+
+    Ok(String::from_utf8_lossy(&command_output.stdout).into_owned())
+}
+
+fn parse_entry(entry: &serde_json::Value) -> Option<VideoMetadata> {
+    // flat playlist entries of older yt-dlp versions only carry the video id
+    let url = match entry["url"].as_str() {
+        Some(url) if url.starts_with("http") => url.to_string(),
+        _ => format!("https://www.youtube.com/watch?v={}", entry["id"].as_str()?),
+    };
+    Some(VideoMetadata::new(&url, &url, None).with_info(entry))
+}
@@ -0,0 +1,18 @@
+use crate::prelude::*;
+
+/// Creates an empty temporary directory unique to the test and the process, leftovers of an aborted run are removed
+pub fn test_dir(module: &str, name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("kce_{}_{}_{}", module, name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Writes the files with their contents below the directory, missing parent directories are created
+pub fn write_files(dir: &Path, files: &[(&str, &str)]) {
+    for (file, content) in files {
+        let path = dir.join(file);
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(path, content).unwrap();
+    }
+}